@@ -1,25 +1,138 @@
-use crate::synth::{Msg, SharedBus, Synth};
+use crate::synth::{LevelMeter, Msg, Retired, SharedBus, Synth, Telemetry};
 
 pub const QUANTUM: usize = 128;
 
-/// Render a mono block into `out`, draining pending bus messages first.
-/// Applies simple clamping to avoid clipping.
-pub fn render_block(synth: &mut Synth, bus: &SharedBus, out: &mut [f32]) {
-    // Drain control messages once per block
-    while let Some(msg) = bus.q.pop() {
-        match msg {
-            Msg::NoteOn { note } => synth.note_on(note),
-            Msg::NoteOff { note } => synth.note_off(note),
-            Msg::SetMasterVolume(v) => synth.set_master_volume(v),
-            Msg::SetAdsr { a, d, s, r } => synth.set_adsr(a, d, s, r),
-            Msg::SetWaveform(wf) => synth.set_waveform(wf),
-            Msg::SetFilter(ft) => synth.set_filter(ft),
+/// Apply a single control message to the synth.
+/// Buffers replaced (or rejected) by the message are handed back to the UI thread via
+/// `bus.retired`.
+pub fn apply_msg(synth: &mut Synth, bus: &SharedBus, msg: Msg) {
+    match msg {
+        Msg::NoteOn { note, velocity } => synth.note_on(note, velocity),
+        Msg::NoteOff { note } => synth.note_off(note),
+        Msg::SetMasterVolume(v) => synth.set_master_volume(v),
+        Msg::SetAdsr { a, d, s, r } => synth.set_adsr(a, d, s, r),
+        Msg::SetVoiceType(vt) => synth.set_voice_type(vt),
+        Msg::SetWaveform(wf) => synth.set_waveform(wf),
+        Msg::SetWavetable(table) => {
+            if let Some(old) = synth.set_wavetable(table) {
+                bus.retire(Retired::Wavetable(old));
+            }
         }
+        Msg::SetSample(sample) => {
+            if let Some(old) = synth.set_sample(sample) {
+                bus.retire(Retired::Sample(old));
+            }
+        }
+        Msg::SetInstrument(inst) => {
+            if let Some(old) = synth.set_instrument(inst) {
+                bus.retire(Retired::Instrument(old));
+            }
+        }
+        Msg::SetFilter(slot, ft) => synth.set_filter(slot, ft),
+        Msg::SetFilterRouting(routing) => synth.set_filter_routing(routing),
+        Msg::SetVowelMod(m) => synth.set_vowel_mod(m),
+        Msg::SetFilterKeyTrack { amount, center } => synth.set_filter_key_track(amount, center),
+        Msg::SetSubOsc(sub) => synth.set_sub_osc(sub),
+        Msg::SetPhaseMode(mode) => synth.set_phase_mode(mode),
+        Msg::SetSmoothing(sec) => synth.set_smoothing(sec),
+        Msg::SetStereo(stereo) => synth.set_stereo(stereo),
+        Msg::SetEq(params) => synth.set_eq(params),
+        Msg::SetCompressor(params) => synth.set_compressor(params),
+        Msg::SetLimiter(params) => synth.set_limiter(params),
+        Msg::LoadFx(slot, fx) => {
+            if let Some(old) = synth.load_fx(slot, fx) {
+                bus.retire(Retired::Effect(old));
+            }
+        }
+        Msg::SetFx(slot, ft) => synth.set_fx(slot, ft),
+        Msg::SetFxMix(slot, mix) => synth.set_fx_mix(slot, mix),
+        Msg::SetFxBypass(slot, bypass) => synth.set_fx_bypass(slot, bypass),
+        Msg::SetFxOrder(order) => synth.set_fx_order(order),
+        Msg::SetTempo(bpm) => synth.set_tempo(bpm),
     }
+}
 
-    for s in out.iter_mut() {
-        let v = synth.next_sample();
-        *s = v.clamp(-1.0, 1.0);
+/// Render `frames` frames, applying each queued message at the sample offset matching its
+/// timestamp.
+///
+/// Messages sent between the previous call and this one are spread over this block in
+/// proportion to when they were sent, so events keep their relative timing at a constant
+/// one-block latency instead of snapping to block boundaries.
+/// Afterwards a [`Telemetry`] snapshot of the block is sent back to the UI.
+fn render_timed(
+    synth: &mut Synth,
+    bus: &SharedBus,
+    frames: usize,
+    mut write: impl FnMut(usize, [f32; 2]),
+) {
+    let (start, now) = bus.begin_block();
+    let span = now - start;
+    let mut meter = LevelMeter::default();
+    let mut render = |i: usize, synth: &mut Synth| {
+        let frame = synth.next_frame();
+        meter.add(frame);
+        write(i, frame);
+    };
+    let mut i = 0;
+    // Events sent after `now` (while this block renders) wait for the next block.
+    while let Some(ev) = bus.next_event(now) {
+        let at = if span > 0.0 {
+            ((ev.time - start) / span * frames as f64).clamp(0.0, frames as f64) as usize
+        } else {
+            0
+        };
+        while i < at {
+            render(i, synth);
+            i += 1;
+        }
+        apply_msg(synth, bus, ev.msg);
+    }
+    while i < frames {
+        render(i, synth);
+        i += 1;
     }
+    if let Some(old) = synth.take_faded_instrument() {
+        bus.retire(Retired::Instrument(old));
+    }
+    bus.report_limiter_gain(synth.take_limiter_gain());
+    bus.report_compressor_gain(synth.take_compressor_gain());
+
+    let mut t = Telemetry::default();
+    synth.voice_telemetry(&mut t);
+    (t.peak, t.rms) = meter.levels();
+    let block_sec = frames as f64 / bus.sample_rate() as f64;
+    if block_sec > 0.0 {
+        t.cpu = ((bus.now() - now) / block_sec) as f32;
+    }
+    // Only messages the UI failed to queue; leaked retired buffers are tracked by
+    // `bus.leaked()` and are not a sign of lost input.
+    t.dropped = bus.dropped();
+    bus.report_telemetry(t);
+}
+
+/// Render a planar stereo block into `left` / `right`, applying bus messages at their offsets.
+/// The synth's master limiter keeps the output within ±1.0.
+pub fn render_block(synth: &mut Synth, bus: &SharedBus, left: &mut [f32], right: &mut [f32]) {
+    let frames = left.len().min(right.len());
+    render_timed(synth, bus, frames, |i, [l, r]| {
+        left[i] = l;
+        right[i] = r;
+    });
+}
+
+/// Render interleaved frames of `channels` channels into `out`.
+/// Mono devices get the L/R mix; extra channels repeat the L/R pair.
+pub fn render_interleaved(synth: &mut Synth, bus: &SharedBus, out: &mut [f32], channels: usize) {
+    let channels = channels.max(1);
+    render_timed(synth, bus, out.len() / channels, |i, [l, r]| {
+        let frame = &mut out[i * channels..(i + 1) * channels];
+        if let [mono] = frame {
+            *mono = 0.5 * (l + r);
+            return;
+        }
+        for (ch, s) in frame.iter_mut().enumerate() {
+            *s = if ch % 2 == 0 { l } else { r };
+        }
+    });
 }
 
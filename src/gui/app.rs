@@ -1,6 +1,22 @@
-use crate::synth::{FilterType, Msg, Note, SharedBus, Waveform};
+use std::{sync::Arc, time::Duration};
+
+use crate::synth::{
+    ClipMode, CombMode, DualFilter, FilterRouting, FilterSlot, FilterType, GrainParams,
+    LimiterParams, LoopMode, MAX_CUTOFF, MAX_STAGES, MIN_CUTOFF, Msg, Note, PhaseMode, PluckParams,
+    SampleParams, SharedBus, Spectrum, SpreadMode, Stereo, SubOsc, SubShape, VoiceType, VowelMod,
+    VowelSet, Waveform, hz_name, hz_to_midi, midi_name, midi_to_hz, parse_note_name,
+};
 use eframe::{App, Frame, egui};
 
+use crate::gui::{
+    fx::FxRackUi,
+    loader::{Loaded, Loader, Source},
+    master::MasterBusUi,
+    meters::MetersUi,
+};
+
+const WAVETABLE_INTERVAL: f64 = 0.1; // 倍音の編集中にテーブルを作り直す最短の間隔[秒]
+
 pub struct EguiUi {
     bus: SharedBus,
     master: f32,
@@ -8,8 +24,87 @@ pub struct EguiUi {
     decay: f32,
     sustain: f32,
     release: f32,
+    voice: VoiceUi,
+    velocity: u8, // キーボードから送るベロシティ
     waveform: WaveformUi,
-    filter: FilterUi,
+    sub: SubOscUi,
+    phase_mode: PhaseMode,
+    filter: FilterBankUi,
+    smoothing_ms: f32, // パラメータ変更の平滑化時間
+    stereo: Stereo,
+    fx: FxRackUi,
+    master_bus: MasterBusUi,
+    meters: MetersUi,
+    limiter: LimiterParams,
+    gain_reduction_db: f32, // 表示用（ゆっくり戻す）
+    loader: Loader,         // WAV / SFZ の読み込み（UI スレッドの外で行う）
+    wavetable_dirty: bool,  // 倍音を編集したがテーブルをまだ送っていない
+    wavetable_sent: f64,    // 最後にテーブルを送った時刻[秒]
+}
+
+#[derive(Clone, PartialEq)]
+pub enum VoiceTypeUi {
+    Osc,
+    Pluck,
+    Sample,
+    Instrument,
+    Granular,
+}
+
+#[derive(Clone)]
+pub struct VoiceUi {
+    voice_type: VoiceTypeUi,
+    pluck_params: PluckParams,
+    sample: SampleUi,
+    instrument: InstrumentUi,
+    grain_params: GrainParams,
+}
+
+impl From<VoiceUi> for VoiceType {
+    fn from(ui: VoiceUi) -> Self {
+        match ui.voice_type {
+            VoiceTypeUi::Osc => VoiceType::Osc,
+            VoiceTypeUi::Pluck => VoiceType::Pluck(ui.pluck_params),
+            VoiceTypeUi::Sample => VoiceType::Sample(ui.sample.params()),
+            VoiceTypeUi::Instrument => VoiceType::Instrument,
+            VoiceTypeUi::Granular => VoiceType::Granular(GrainParams {
+                root: ui.sample.root,
+                ..ui.grain_params
+            }),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SampleUi {
+    root: u8,
+    mode: LoopMode,
+    loop_start: f32, // サンプル長に対する割合
+    loop_end: f32,
+    crossfade_ms: f32,
+    path: String,
+    status: String, // 読み込んだファイルの情報 or エラー
+    len: usize,
+    sr: f32,
+}
+
+#[derive(Clone)]
+pub struct InstrumentUi {
+    path: String,
+    status: String,
+}
+
+impl SampleUi {
+    fn params(&self) -> SampleParams {
+        let frames = |r: f32| (r.clamp(0.0, 1.0) * self.len as f32) as usize;
+        SampleParams {
+            root: self.root,
+            mode: self.mode,
+            loop_start: frames(self.loop_start),
+            loop_end: frames(self.loop_end.max(self.loop_start)),
+            crossfade: (self.crossfade_ms * 0.001 * self.sr) as usize,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -18,6 +113,7 @@ pub enum WaveformTypeUi {
     Square,
     Saw,
     Triangle,
+    Additive,
 }
 
 impl WaveformTypeUi {
@@ -27,6 +123,7 @@ impl WaveformTypeUi {
             WaveformTypeUi::Square => Waveform::Square { pulse_width },
             WaveformTypeUi::Saw => Waveform::Sawtooth,
             WaveformTypeUi::Triangle => Waveform::Triangle { curve },
+            WaveformTypeUi::Additive => Waveform::Additive,
         }
     }
 }
@@ -36,6 +133,8 @@ pub struct WaveformUi {
     pulse_width: f32,
     curve: f32,
     waveform_type: WaveformTypeUi,
+    spectrum: Spectrum,
+    edit_phase: bool, // 倍音エディタで位相を編集するか
 }
 
 impl From<WaveformUi> for Waveform {
@@ -44,19 +143,109 @@ impl From<WaveformUi> for Waveform {
     }
 }
 
+#[derive(Clone)]
+pub struct SubOscUi {
+    show: bool,
+    sub: SubOsc,
+}
+
+impl From<SubOscUi> for Option<SubOsc> {
+    fn from(ui: SubOscUi) -> Self {
+        ui.show.then_some(ui.sub)
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub enum FilterTypeUi {
     OnePoleLpf,
     TwoPoleLpf,
+    TwoPoleHpf,
+    BandPass,
+    BandPassPeak,
+    Notch,
+    AllPass,
+    Peaking,
+    LowShelf,
+    HighShelf,
+    Svf,
+    Ladder,
+    Formant,
+    CombFf,
+    CombFb,
+    Phaser,
 }
 
 impl FilterTypeUi {
-    fn to_filter_type(&self, cut_off: f32, q: f32) -> FilterType {
+    const ALL: [(FilterTypeUi, &'static str); 16] = [
+        (Self::OnePoleLpf, "OnePoleLpf"),
+        (Self::TwoPoleLpf, "TwoPoleLpf"),
+        (Self::TwoPoleHpf, "TwoPoleHpf"),
+        (Self::BandPass, "BPF (skirt)"),
+        (Self::BandPassPeak, "BPF (peak)"),
+        (Self::Notch, "Notch"),
+        (Self::AllPass, "AllPass"),
+        (Self::Peaking, "Peaking"),
+        (Self::LowShelf, "LowShelf"),
+        (Self::HighShelf, "HighShelf"),
+        (Self::Svf, "SVF"),
+        (Self::Ladder, "Ladder 24dB"),
+        (Self::Formant, "Formant"),
+        (Self::CombFf, "Comb (FF)"),
+        (Self::CombFb, "Comb (FB)"),
+        (Self::Phaser, "Phaser"),
+    ];
+
+    fn to_filter_type(&self, ui: &FilterUi) -> FilterType {
+        let FilterUi {
+            cutoff: cut_off,
+            q,
+            gain_db,
+            morph,
+            ..
+        } = *ui;
         match self {
             Self::OnePoleLpf => FilterType::OnePoleLpf(cut_off),
             Self::TwoPoleLpf => FilterType::TwoPoleLpf(cut_off, q),
+            Self::TwoPoleHpf => FilterType::TwoPoleHpf(cut_off, q),
+            Self::BandPass => FilterType::BandPass(cut_off, q),
+            Self::BandPassPeak => FilterType::BandPassPeak(cut_off, q),
+            Self::Notch => FilterType::Notch(cut_off, q),
+            Self::AllPass => FilterType::AllPass(cut_off, q),
+            Self::Peaking => FilterType::Peaking(cut_off, q, gain_db),
+            Self::LowShelf => FilterType::LowShelf(cut_off, q, gain_db),
+            Self::HighShelf => FilterType::HighShelf(cut_off, q, gain_db),
+            Self::Svf => FilterType::Svf(cut_off, q, morph),
+            Self::Ladder => FilterType::Ladder(cut_off, ui.resonance, ui.drive),
+            Self::Formant => FilterType::Formant(ui.vowel, ui.vowel_set),
+            Self::CombFf => FilterType::Comb(ui.tune, ui.comb_gain, CombMode::FeedForward),
+            Self::CombFb => FilterType::Comb(ui.tune, ui.comb_gain, CombMode::Feedback),
+            Self::Phaser => FilterType::Phaser(cut_off, ui.feedback, ui.stages),
         }
     }
+
+    fn has_q(&self) -> bool {
+        !matches!(
+            self,
+            Self::OnePoleLpf
+                | Self::Ladder
+                | Self::Formant
+                | Self::CombFf
+                | Self::CombFb
+                | Self::Phaser
+        )
+    }
+
+    fn has_cutoff(&self) -> bool {
+        !matches!(self, Self::Formant | Self::CombFf | Self::CombFb)
+    }
+
+    fn is_comb(&self) -> bool {
+        matches!(self, Self::CombFf | Self::CombFb)
+    }
+
+    fn has_gain(&self) -> bool {
+        matches!(self, Self::Peaking | Self::LowShelf | Self::HighShelf)
+    }
 }
 
 #[derive(Clone)]
@@ -64,12 +253,94 @@ pub struct FilterUi {
     show: bool,
     cutoff: f32,
     q: f32,
+    gain_db: f32,
+    morph: f32,
+    resonance: f32,
+    drive: f32,
+    vowel: f32,
+    vowel_set: VowelSet,
+    tune: f32, // コムのノートからの音程[半音]
+    comb_gain: f32,
+    feedback: f32, // フェイザー
+    stages: u8,
     filter_type: FilterTypeUi,
 }
 
+impl Default for FilterUi {
+    fn default() -> Self {
+        Self {
+            show: false,
+            cutoff: 1000.0,
+            q: 5.0,
+            gain_db: 6.0,
+            morph: 0.0,
+            resonance: 0.3,
+            drive: 0.0,
+            vowel: 0.0,
+            vowel_set: VowelSet::Male,
+            tune: 0.0,
+            comb_gain: 0.7,
+            feedback: 0.5,
+            stages: 4,
+            filter_type: FilterTypeUi::OnePoleLpf,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RoutingUi {
+    Serial,
+    Parallel,
+    Split,
+}
+
+/// 2つのフィルタスロットと、その接続・キートラッキングの設定
+#[derive(Clone)]
+pub struct FilterBankUi {
+    slots: [FilterUi; 2],
+    unit: CutoffUnit,
+    key_track: f32, // 0.0〜1.0
+    key_center: u8,
+    routing: RoutingUi,
+    balance: f32,
+    main_slot: FilterSlot,
+    sub_slot: FilterSlot,
+    vowel_mod: VowelMod,
+}
+
+impl FilterBankUi {
+    fn routing(&self) -> FilterRouting {
+        match self.routing {
+            RoutingUi::Serial => FilterRouting::Serial,
+            RoutingUi::Parallel => FilterRouting::Parallel {
+                balance: self.balance,
+            },
+            RoutingUi::Split => FilterRouting::Split {
+                main: self.main_slot,
+                sub: self.sub_slot,
+                balance: self.balance,
+            },
+        }
+    }
+
+    /// 表示用のフィルタ（キートラッキングの基準ノートで鳴らしたときのもの）
+    fn dual_filter(&self, sr: f32) -> DualFilter {
+        let types = (self.slots.clone()).map(|slot| slot.show.then(|| slot.into()));
+        DualFilter::new(types, sr, midi_to_hz(self.key_center as f32))
+    }
+}
+
+/// カットオフの表示単位
+#[derive(Clone, Copy, PartialEq)]
+pub enum CutoffUnit {
+    Hz,
+    Semitones, // キートラッキングの基準ノートからの半音数
+    Note,
+}
+
 impl From<FilterUi> for FilterType {
     fn from(ui: FilterUi) -> Self {
-        ui.filter_type.to_filter_type(ui.cutoff, ui.q)
+        ui.filter_type.to_filter_type(&ui)
     }
 }
 
@@ -82,41 +353,426 @@ impl EguiUi {
             decay: 0.5,
             sustain: 1.0,
             release: 0.5,
+            voice: VoiceUi {
+                voice_type: VoiceTypeUi::Osc,
+                pluck_params: PluckParams::default(),
+                sample: SampleUi {
+                    root: 60,
+                    mode: LoopMode::NoLoop,
+                    loop_start: 0.0,
+                    loop_end: 1.0,
+                    crossfade_ms: 10.0,
+                    path: String::new(),
+                    status: "No sample loaded (drop a WAV file)".to_owned(),
+                    len: 0,
+                    sr: 0.0,
+                },
+                instrument: InstrumentUi {
+                    path: String::new(),
+                    status: "No instrument loaded (drop an SFZ with its samples)".to_owned(),
+                },
+                grain_params: GrainParams::default(),
+            },
+            velocity: 100,
             waveform: WaveformUi {
                 pulse_width: 0.5,
                 curve: 0.0,
                 waveform_type: WaveformTypeUi::Sine,
+                spectrum: Spectrum::saw(),
+                edit_phase: false,
             },
-            filter: FilterUi {
+            sub: SubOscUi {
                 show: false,
-                cutoff: 1000.0,
-                q: 5.0,
-                filter_type: FilterTypeUi::OnePoleLpf,
+                sub: SubOsc::default(),
+            },
+            phase_mode: PhaseMode::Reset,
+            filter: FilterBankUi {
+                slots: Default::default(),
+                unit: CutoffUnit::Hz,
+                key_track: 0.0,
+                key_center: 60,
+                routing: RoutingUi::Serial,
+                balance: 0.5,
+                main_slot: FilterSlot::One,
+                sub_slot: FilterSlot::Two,
+                vowel_mod: VowelMod::default(),
             },
+            smoothing_ms: 20.0,
+            stereo: Stereo::default(),
+            fx: FxRackUi::default(),
+            master_bus: MasterBusUi::default(),
+            meters: MetersUi::default(),
+            limiter: LimiterParams::default(),
+            gain_reduction_db: 0.0,
+            loader: Loader::default(),
+            wavetable_dirty: false,
+            wavetable_sent: 0.0,
         };
         // Push initial params
-        let _ = ui.bus.q.push(Msg::SetMasterVolume(ui.master));
-        let _ = ui.bus.q.push(Msg::SetAdsr {
+        let _ = ui.bus.send(Msg::SetMasterVolume(ui.master));
+        let _ = ui.bus.send(Msg::SetAdsr {
             a: ui.attack,
             d: ui.decay,
             s: ui.sustain,
             r: ui.release,
         });
-        let _ = ui.bus.q.push(Msg::SetWaveform(ui.waveform.clone().into()));
+        let _ = ui.bus.send(Msg::SetWaveform(ui.waveform.clone().into()));
+        ui.push_wavetable();
         ui
     }
+
+    /// 読み込みが終わった WAV / SFZ をオーディオスレッドへ送る。サンプルを読み込んだら true
+    fn apply_loaded(&mut self, loaded: Loaded) -> bool {
+        match loaded {
+            Loaded::Sample { name, result } => {
+                let ui = &mut self.voice.sample;
+                match result {
+                    Ok(sample) => {
+                        ui.len = sample.data.len();
+                        ui.sr = sample.sr;
+                        ui.status = format!(
+                            "{name}: {:.2}s @ {}Hz",
+                            ui.len as f32 / sample.sr,
+                            sample.sr
+                        );
+                        let _ = self.bus.send(Msg::SetSample(Arc::new(sample)));
+                        true
+                    }
+                    Err(e) => {
+                        ui.status = format!("{name}: {e}");
+                        false
+                    }
+                }
+            }
+            Loaded::Instrument { name, result } => {
+                let ui = &mut self.voice.instrument;
+                match result {
+                    Ok(inst) => {
+                        ui.status = format!(
+                            "{name}: {} regions, {} samples",
+                            inst.regions.len(),
+                            inst.samples.len()
+                        );
+                        let _ = self.bus.send(Msg::SetInstrument(Arc::new(inst)));
+                    }
+                    Err(e) => ui.status = format!("{name}: {e}"),
+                }
+                false
+            }
+        }
+    }
+
+    fn load_sample(&mut self, ctx: &egui::Context, name: String, src: Source) {
+        self.voice.sample.status = format!("Loading {name}...");
+        self.loader.sample(ctx, name, src);
+    }
+
+    /// SFZ のサンプルは others（ファイル名で照合）か SFZ からの相対パスで探す
+    fn load_instrument(
+        &mut self,
+        ctx: &egui::Context,
+        name: String,
+        src: Source,
+        others: Vec<(String, Source)>,
+    ) {
+        let dir = std::path::Path::new(&name)
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_default();
+        self.voice.instrument.status = format!("Loading {name}...");
+        self.loader.instrument(ctx, name, src, dir, others);
+    }
+
+    /// ドロップされたファイルを読み込む
+    /// SFZ のサンプルは一緒にドロップされたファイル（Web）か SFZ からの相対パス（ネイティブ）で探す
+    fn load_dropped(&mut self, ctx: &egui::Context, files: Vec<egui::DroppedFile>) {
+        let files: Vec<(String, Source)> = files
+            .into_iter()
+            .filter_map(|file| {
+                let name = match &file.path {
+                    Some(path) => path.display().to_string(),
+                    None => file.name.clone(),
+                };
+                let src = match (file.bytes, file.path) {
+                    (Some(bytes), _) => Source::Bytes(bytes),
+                    (None, Some(path)) => Source::Path(path),
+                    (None, None) => return None,
+                };
+                Some((name, src))
+            })
+            .collect();
+
+        let is_sfz = |name: &str| name.to_ascii_lowercase().ends_with(".sfz");
+        let has_sfz = files.iter().any(|(n, _)| is_sfz(n));
+        for (name, src) in files.iter() {
+            if is_sfz(name) {
+                self.load_instrument(ctx, name.clone(), src.clone(), files.clone());
+            } else if !has_sfz {
+                self.load_sample(ctx, name.clone(), src.clone());
+            }
+        }
+    }
+
+    fn push_wavetable(&self) {
+        let table = self.waveform.spectrum.to_wavetable();
+        let _ = self.bus.send(Msg::SetWavetable(Arc::new(table)));
+    }
+}
+
+/// フィルタ1スロット分の設定UI（変更があれば true）
+fn filter_slot_ui(
+    ui: &mut egui::Ui,
+    index: usize,
+    slot: &mut FilterUi,
+    unit: CutoffUnit,
+    center_hz: f32,
+) -> bool {
+    let mut changed = ui
+        .checkbox(&mut slot.show, format!("Filter {index}"))
+        .changed();
+    if !slot.show {
+        return changed;
+    }
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Type:");
+        for (ft, label) in FilterTypeUi::ALL {
+            changed |= ui
+                .selectable_value(&mut slot.filter_type, ft, label)
+                .changed();
+        }
+    });
+    if slot.filter_type.has_cutoff() {
+        ui.horizontal(|ui| {
+            ui.label("Cutoff:");
+            let slider =
+                egui::Slider::new(&mut slot.cutoff, MIN_CUTOFF..=MAX_CUTOFF).logarithmic(true);
+            // 表示と入力を単位ごとに切り替える（内部値は常に Hz）
+            let slider = match unit {
+                CutoffUnit::Hz => slider.suffix(" Hz"),
+                CutoffUnit::Semitones => slider
+                    .custom_formatter(move |hz, _| {
+                        let st = hz_to_midi(hz as f32) - hz_to_midi(center_hz);
+                        format!("{st:+.1} st")
+                    })
+                    .custom_parser(move |s| {
+                        let st = s.trim().trim_end_matches("st").trim().parse::<f32>();
+                        st.ok()
+                            .map(|st| (center_hz * 2.0f32.powf(st / 12.0)) as f64)
+                    }),
+                CutoffUnit::Note => slider
+                    .custom_formatter(|hz, _| hz_name(hz as f32))
+                    .custom_parser(|s| parse_note_name(s).map(|n| midi_to_hz(n as f32) as f64)),
+            };
+            changed |= ui.add(slider).changed();
+        });
+    }
+    if slot.filter_type.has_q() {
+        ui.horizontal(|ui| {
+            ui.label("Resonance (Q):");
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.q, 0.1..=10.0).text("Q"))
+                .changed();
+        });
+    }
+    if slot.filter_type == FilterTypeUi::Svf {
+        ui.horizontal(|ui| {
+            ui.label("Morph (LP-BP-HP):");
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.morph, 0.0..=1.0).text("Morph"))
+                .changed();
+        });
+    }
+    if slot.filter_type == FilterTypeUi::Ladder {
+        changed |= ui
+            .add(egui::Slider::new(&mut slot.resonance, 0.0..=1.0).text("Resonance"))
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut slot.drive, 0.0..=1.0).text("Drive"))
+            .changed();
+    }
+    if slot.filter_type == FilterTypeUi::Formant {
+        ui.horizontal(|ui| {
+            ui.label("Vowel:");
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.vowel, 0.0..=4.0).custom_formatter(vowel_name))
+                .changed();
+            for (set, label) in [(VowelSet::Male, "Male"), (VowelSet::Female, "Female")] {
+                changed |= ui
+                    .selectable_value(&mut slot.vowel_set, set, label)
+                    .changed();
+            }
+        });
+    }
+    if slot.filter_type.is_comb() {
+        ui.horizontal(|ui| {
+            ui.label("Tune:");
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.tune, -24.0..=24.0).suffix(" st"))
+                .changed();
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.comb_gain, -0.99..=0.99).text("Gain"))
+                .changed();
+        });
+    }
+    if slot.filter_type == FilterTypeUi::Phaser {
+        ui.horizontal(|ui| {
+            ui.label("Stages:");
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.stages, 2..=MAX_STAGES).step_by(2.0))
+                .changed();
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.feedback, -0.95..=0.95).text("Feedback"))
+                .changed();
+        });
+    }
+    if slot.filter_type.has_gain() {
+        ui.horizontal(|ui| {
+            ui.label("Gain:");
+            changed |= ui
+                .add(egui::Slider::new(&mut slot.gain_db, -24.0..=24.0).text("dB"))
+                .changed();
+        });
+    }
+    changed
+}
+
+/// フィルタ全体の周波数応答を描く（振幅: 太線, 位相: 細線）
+fn response_plot(ui: &mut egui::Ui, filter: &DualFilter, routing: FilterRouting, sr: f32) {
+    const DB_MIN: f32 = -36.0;
+    const DB_MAX: f32 = 24.0;
+    let size = egui::vec2(ui.available_width(), 120.0);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    let top = MAX_CUTOFF.min(0.5 * sr);
+    let span = (top / MIN_CUTOFF).ln();
+    let x_of = |hz: f32| rect.left() + rect.width() * (hz / MIN_CUTOFF).ln() / span;
+    let y_of = |db: f32| {
+        rect.bottom() - rect.height() * ((db - DB_MIN) / (DB_MAX - DB_MIN)).clamp(0.0, 1.0)
+    };
+    let grid = ui.visuals().widgets.noninteractive.bg_stroke;
+    let weak = ui.visuals().weak_text_color();
+    for (hz, label) in [(100.0, "100"), (1000.0, "1k"), (10000.0, "10k")] {
+        let x = x_of(hz);
+        painter.vline(x, rect.y_range(), grid);
+        let pos = egui::pos2(x + 2.0, rect.top());
+        painter.text(
+            pos,
+            egui::Align2::LEFT_TOP,
+            label,
+            egui::FontId::proportional(10.0),
+            weak,
+        );
+    }
+    painter.hline(rect.x_range(), y_of(0.0), grid);
+
+    let n = (rect.width() as usize).max(2);
+    let (mut mag, mut phase) = (Vec::with_capacity(n), Vec::with_capacity(n));
+    for i in 0..n {
+        let hz = MIN_CUTOFF * (span * i as f32 / (n - 1) as f32).exp();
+        let h = filter.response(routing, sr, hz);
+        let x = x_of(hz);
+        mag.push(egui::pos2(x, y_of(20.0 * h.abs().max(1e-10).log10())));
+        let p = h.arg() / std::f32::consts::PI;
+        phase.push(egui::pos2(x, rect.center().y - 0.5 * rect.height() * p));
+    }
+    painter.add(egui::Shape::line(phase, egui::Stroke::new(1.0, weak)));
+    let stroke = egui::Stroke::new(1.5, ui.visuals().selection.stroke.color);
+    painter.add(egui::Shape::line(mag, stroke));
+}
+
+/// 母音の位置の表示（例: 1.3 → "E→I 30%"）
+fn vowel_name(v: f64, _: std::ops::RangeInclusive<usize>) -> String {
+    const NAMES: [&str; 5] = ["A", "E", "I", "O", "U"];
+    let i = (v.floor() as usize).min(4);
+    let t = v - i as f64;
+    if t < 0.005 || i == 4 {
+        NAMES[i].to_owned()
+    } else {
+        format!("{}→{} {:.0}%", NAMES[i], NAMES[i + 1], t * 100.0)
+    }
+}
+
+/// 倍音ごとの値（0.0〜1.0）を棒グラフで表示し、ドラッグで編集する
+fn harmonic_editor(ui: &mut egui::Ui, values: &mut [f32]) -> bool {
+    let size = egui::vec2(ui.available_width(), 80.0);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+    let mut changed = false;
+    if response.is_pointer_button_down_on()
+        && let Some(pos) = response.interact_pointer_pos()
+    {
+        let idx = ((pos.x - rect.left()) / rect.width() * values.len() as f32) as usize;
+        let v = ((rect.bottom() - pos.y) / rect.height()).clamp(0.0, 1.0);
+        if let Some(slot) = values.get_mut(idx.min(values.len().saturating_sub(1)))
+            && *slot != v
+        {
+            *slot = v;
+            changed = true;
+        }
+    }
+
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+    let w = rect.width() / values.len().max(1) as f32;
+    for (i, v) in values.iter().enumerate() {
+        let x0 = rect.left() + i as f32 * w;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(x0 + 0.5, rect.bottom() - v * rect.height()),
+            egui::pos2(x0 + w - 0.5, rect.bottom()),
+        );
+        painter.rect_filled(bar, 0.0, ui.visuals().selection.bg_fill);
+    }
+    changed
 }
 
 impl App for EguiUi {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut Frame) {
+        // オーディオスレッドから戻ってきた古いバッファを解放
+        self.bus.collect_retired();
+
+        // 読み込みの終わった WAV / SFZ を反映し、ドロップされたものを読み込み始める
+        let mut sample_loaded = false;
+        while let Some(loaded) = self.loader.poll() {
+            sample_loaded |= self.apply_loaded(loaded);
+        }
+        let dropped = ctx.input(|i| i.raw.dropped_files.clone());
+        if !dropped.is_empty() {
+            self.load_dropped(ctx, dropped);
+        }
+
         // Read current input events and whether UI wants keyboard focus
         let events = ctx.input(|i| i.events.clone());
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Synth Controls");
-            let mut changed = (false, false, false, false);
+            let mut changed = (
+                false,
+                false,
+                false,
+                false,
+                false,
+                false,
+                false,
+                sample_loaded,
+                false,
+                false,
+            );
             changed.0 |= ui
                 .add(egui::Slider::new(&mut self.master, 0.0..=1.0).text("Master"))
                 .changed();
+            // 出力レベルと鳴っている鍵盤
+            self.meters.show(ui, &self.bus);
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.smoothing_ms, 0.0..=200.0)
+                        .text("Smoothing")
+                        .suffix(" ms"),
+                )
+                .changed()
+            {
+                let _ = self.bus.send(Msg::SetSmoothing(self.smoothing_ms / 1000.0));
+            }
+            ui.add(egui::Slider::new(&mut self.velocity, 1..=127).text("Velocity"));
             changed.1 |= ui
                 .add(egui::Slider::new(&mut self.attack, 0.0..=2.0).text("Attack"))
                 .changed();
@@ -130,6 +786,142 @@ impl App for EguiUi {
                 .add(egui::Slider::new(&mut self.release, 0.0..=2.0).text("Release"))
                 .changed();
 
+            // ボイスの音源
+            ui.horizontal(|ui| {
+                ui.label("Voice:");
+                changed.7 |= ui
+                    .selectable_value(&mut self.voice.voice_type, VoiceTypeUi::Osc, "Osc")
+                    .changed();
+                changed.7 |= ui
+                    .selectable_value(&mut self.voice.voice_type, VoiceTypeUi::Pluck, "Pluck")
+                    .changed();
+                changed.7 |= ui
+                    .selectable_value(&mut self.voice.voice_type, VoiceTypeUi::Sample, "Sample")
+                    .changed();
+                changed.7 |= ui
+                    .selectable_value(
+                        &mut self.voice.voice_type,
+                        VoiceTypeUi::Instrument,
+                        "Instrument",
+                    )
+                    .changed();
+                changed.7 |= ui
+                    .selectable_value(
+                        &mut self.voice.voice_type,
+                        VoiceTypeUi::Granular,
+                        "Granular",
+                    )
+                    .changed();
+            });
+            if self.voice.voice_type == VoiceTypeUi::Instrument {
+                ui.label(&self.voice.instrument.status);
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.voice.instrument.path);
+                    if ui.button("Load SFZ").clicked() {
+                        let path = std::path::PathBuf::from(&self.voice.instrument.path);
+                        let name = path.display().to_string();
+                        self.load_instrument(ctx, name, Source::Path(path), Vec::new());
+                    }
+                });
+            }
+            // サンプル再生とグラニュラーは同じサンプルを使う
+            if matches!(
+                self.voice.voice_type,
+                VoiceTypeUi::Sample | VoiceTypeUi::Granular
+            ) {
+                ui.label(&self.voice.sample.status);
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.voice.sample.path);
+                    if ui.button("Load").clicked() {
+                        let path = self.voice.sample.path.clone();
+                        self.load_sample(ctx, path.clone(), Source::Path(path.into()));
+                    }
+                });
+                let smp = &mut self.voice.sample;
+                changed.7 |= ui
+                    .add(
+                        egui::Slider::new(&mut smp.root, 0..=127)
+                            .text("Root")
+                            .custom_formatter(|n, _| midi_name(n as u8)),
+                    )
+                    .changed();
+            }
+            if self.voice.voice_type == VoiceTypeUi::Sample {
+                let smp = &mut self.voice.sample;
+                ui.horizontal(|ui| {
+                    ui.label("Mode:");
+                    changed.7 |= ui
+                        .selectable_value(&mut smp.mode, LoopMode::NoLoop, "No loop")
+                        .changed();
+                    changed.7 |= ui
+                        .selectable_value(&mut smp.mode, LoopMode::Loop, "Loop")
+                        .changed();
+                    changed.7 |= ui
+                        .selectable_value(&mut smp.mode, LoopMode::Sustain, "Sustain loop")
+                        .changed();
+                    changed.7 |= ui
+                        .selectable_value(&mut smp.mode, LoopMode::OneShot, "One-shot")
+                        .changed();
+                });
+                if matches!(smp.mode, LoopMode::Loop | LoopMode::Sustain) {
+                    changed.7 |= ui
+                        .add(egui::Slider::new(&mut smp.loop_start, 0.0..=1.0).text("Loop start"))
+                        .changed();
+                    changed.7 |= ui
+                        .add(egui::Slider::new(&mut smp.loop_end, 0.0..=1.0).text("Loop end"))
+                        .changed();
+                    changed.7 |= ui
+                        .add(
+                            egui::Slider::new(&mut smp.crossfade_ms, 0.0..=500.0)
+                                .text("Crossfade (ms)"),
+                        )
+                        .changed();
+                }
+            }
+            if self.voice.voice_type == VoiceTypeUi::Granular {
+                let g = &mut self.voice.grain_params;
+                changed.7 |= ui
+                    .add(egui::Slider::new(&mut g.position, 0.0..=1.0).text("Position"))
+                    .changed();
+                changed.7 |= ui
+                    .add(egui::Slider::new(&mut g.spray, 0.0..=1.0).text("Spray"))
+                    .changed();
+                changed.7 |= ui
+                    .add(
+                        egui::Slider::new(&mut g.size_ms, 5.0..=500.0)
+                            .logarithmic(true)
+                            .text("Grain size (ms)"),
+                    )
+                    .changed();
+                changed.7 |= ui
+                    .add(
+                        egui::Slider::new(&mut g.density, 1.0..=200.0)
+                            .logarithmic(true)
+                            .text("Density (/s)"),
+                    )
+                    .changed();
+                changed.7 |= ui
+                    .add(egui::Slider::new(&mut g.pitch, -24.0..=24.0).text("Pitch (st)"))
+                    .changed();
+                changed.7 |= ui
+                    .add(egui::Slider::new(&mut g.scatter, 0.0..=1.0).text("Stereo scatter"))
+                    .changed();
+            }
+            if self.voice.voice_type == VoiceTypeUi::Pluck {
+                let p = &mut self.voice.pluck_params;
+                changed.7 |= ui
+                    .add(egui::Slider::new(&mut p.damping, 0.0..=1.0).text("Damping"))
+                    .changed();
+                changed.7 |= ui
+                    .add(egui::Slider::new(&mut p.brightness, 0.0..=1.0).text("Brightness"))
+                    .changed();
+                changed.7 |= ui
+                    .add(egui::Slider::new(&mut p.decay, 0.05..=10.0).text("Decay (s)"))
+                    .changed();
+            }
+
             ui.horizontal(|ui| {
                 ui.label("Waveform:");
                 changed.2 |= ui
@@ -156,6 +948,13 @@ impl App for EguiUi {
                         "Tri",
                     )
                     .changed();
+                changed.2 |= ui
+                    .selectable_value(
+                        &mut self.waveform.waveform_type,
+                        WaveformTypeUi::Additive,
+                        "Additive",
+                    )
+                    .changed();
             });
 
             // ここを追加: 選択中の波形に応じたパラメータUI
@@ -184,56 +983,264 @@ impl App for EguiUi {
                             .changed();
                     });
                 }
+                WaveformTypeUi::Additive => {
+                    let spectrum = &mut self.waveform.spectrum;
+                    ui.horizontal(|ui| {
+                        ui.label("Harmonics:");
+                        ui.selectable_value(&mut self.waveform.edit_phase, false, "Amp");
+                        ui.selectable_value(&mut self.waveform.edit_phase, true, "Phase");
+                        ui.separator();
+                        if ui.button("Sine").clicked() {
+                            *spectrum = Spectrum::default();
+                            changed.6 = true;
+                        }
+                        if ui.button("Saw").clicked() {
+                            *spectrum = Spectrum::saw();
+                            changed.6 = true;
+                        }
+                        if ui.button("Square").clicked() {
+                            *spectrum = Spectrum::square();
+                            changed.6 = true;
+                        }
+                    });
+                    let values = if self.waveform.edit_phase {
+                        &mut spectrum.phases
+                    } else {
+                        &mut spectrum.amps
+                    };
+                    changed.6 |= harmonic_editor(ui, values);
+                }
                 _ => {}
             }
 
-            // フィルタ選択UIの追加
-            // フィルタのOn/Off
-            ui.label("Filter:");
-            // FilterのOn/Off
-            changed.3 |= ui.checkbox(&mut self.filter.show, "Enabled").changed();
-            if self.filter.show {
+            // サブオシレータ
+            ui.horizontal(|ui| {
+                ui.label("Sub osc:");
+                changed.4 |= ui.checkbox(&mut self.sub.show, "Enabled").changed();
+            });
+            if self.sub.show {
                 ui.horizontal(|ui| {
-                    ui.label("Type:");
-                    changed.3 |= ui
-                        .selectable_value(
-                            &mut self.filter.filter_type,
-                            FilterTypeUi::OnePoleLpf,
-                            "OnePoleLpf",
+                    changed.4 |= ui
+                        .selectable_value(&mut self.sub.sub.shape, SubShape::Square, "Square")
+                        .changed();
+                    changed.4 |= ui
+                        .selectable_value(&mut self.sub.sub.shape, SubShape::Sine, "Sine")
+                        .changed();
+                    ui.separator();
+                    changed.4 |= ui
+                        .selectable_value(&mut self.sub.sub.octave, 1, "-1 oct")
+                        .changed();
+                    changed.4 |= ui
+                        .selectable_value(&mut self.sub.sub.octave, 2, "-2 oct")
+                        .changed();
+                });
+                changed.4 |= ui
+                    .add(egui::Slider::new(&mut self.sub.sub.level, 0.0..=1.0).text("Sub level"))
+                    .changed();
+            }
+
+            // ノートオン時の位相
+            ui.horizontal(|ui| {
+                ui.label("Phase:");
+                changed.5 |= ui
+                    .selectable_value(&mut self.phase_mode, PhaseMode::Reset, "Reset")
+                    .changed();
+                changed.5 |= ui
+                    .selectable_value(&mut self.phase_mode, PhaseMode::Random, "Random")
+                    .changed();
+                changed.5 |= ui
+                    .selectable_value(&mut self.phase_mode, PhaseMode::FreeRun, "Free run")
+                    .changed();
+            });
+
+            // ステレオ（パンとボイスの広がり）
+            ui.horizontal(|ui| {
+                ui.label("Stereo:");
+                changed.8 |= ui
+                    .add(egui::Slider::new(&mut self.stereo.pan, -1.0..=1.0).text("Pan"))
+                    .changed();
+                changed.8 |= ui
+                    .add(egui::Slider::new(&mut self.stereo.width, 0.0..=2.0).text("Width"))
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                changed.8 |= ui
+                    .add(egui::Slider::new(&mut self.stereo.spread, 0.0..=1.0).text("Spread"))
+                    .changed();
+                changed.8 |= ui
+                    .selectable_value(&mut self.stereo.mode, SpreadMode::Key, "By key")
+                    .changed();
+                changed.8 |= ui
+                    .selectable_value(&mut self.stereo.mode, SpreadMode::Alternate, "Alternate")
+                    .changed();
+            });
+
+            // エフェクト
+            self.fx.show(ui, &self.bus);
+
+            // マスターの EQ とコンプレッサー
+            self.master_bus.show(ui, &self.bus);
+
+            // マスターのリミッター
+            ui.horizontal(|ui| {
+                changed.9 |= ui.checkbox(&mut self.limiter.enabled, "Limiter").changed();
+                let gr = self.bus.take_limiter_gain();
+                let db = -20.0 * gr.max(1.0e-6).log10();
+                self.gain_reduction_db = db.max(self.gain_reduction_db - 0.5);
+                ui.add(
+                    egui::ProgressBar::new(self.gain_reduction_db / 24.0)
+                        .desired_width(120.0)
+                        .text(format!("GR {:.1} dB", -self.gain_reduction_db)),
+                );
+            });
+            if self.limiter.enabled {
+                ui.horizontal(|ui| {
+                    changed.9 |= ui
+                        .add(
+                            egui::Slider::new(&mut self.limiter.ceiling_db, -12.0..=0.0)
+                                .text("Ceiling")
+                                .suffix(" dB"),
                         )
                         .changed();
-                    changed.3 |= ui
-                        .selectable_value(
-                            &mut self.filter.filter_type,
-                            FilterTypeUi::TwoPoleLpf,
-                            "TwoPoleLpf",
+                    changed.9 |= ui
+                        .add(
+                            egui::Slider::new(&mut self.limiter.release_ms, 10.0..=1000.0)
+                                .logarithmic(true)
+                                .text("Release")
+                                .suffix(" ms"),
                         )
                         .changed();
                 });
+                changed.9 |= ui
+                    .add(
+                        egui::Slider::new(&mut self.limiter.lookahead_ms, 0.1..=5.0)
+                            .text("Look-ahead")
+                            .suffix(" ms"),
+                    )
+                    .changed();
+            }
+            ui.horizontal(|ui| {
+                ui.label("Clip:");
+                for (mode, label) in [
+                    (ClipMode::Hard, "Hard"),
+                    (ClipMode::Tanh, "Tanh"),
+                    (ClipMode::Cubic, "Cubic"),
+                ] {
+                    changed.9 |= ui
+                        .selectable_value(&mut self.limiter.clip, mode, label)
+                        .changed();
+                }
+            });
+
+            // フィルタ選択UIの追加
+            ui.label("Filter:");
+            let center_hz = midi_to_hz(self.filter.key_center as f32);
+            let unit = self.filter.unit;
+            for (i, slot) in self.filter.slots.iter_mut().enumerate() {
+                ui.push_id(i, |ui| {
+                    changed.3 |= filter_slot_ui(ui, i + 1, slot, unit, center_hz);
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("Cutoff unit:");
+                for (unit, label) in [
+                    (CutoffUnit::Hz, "Hz"),
+                    (CutoffUnit::Semitones, "st"),
+                    (CutoffUnit::Note, "Note"),
+                ] {
+                    ui.selectable_value(&mut self.filter.unit, unit, label);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Key track:");
+                changed.3 |= ui
+                    .add(
+                        egui::Slider::new(&mut self.filter.key_track, 0.0..=1.0)
+                            .custom_formatter(|v, _| format!("{:.0}%", v * 100.0)),
+                    )
+                    .changed();
+                changed.3 |= ui
+                    .add(
+                        egui::Slider::new(&mut self.filter.key_center, 0..=127)
+                            .text("Center")
+                            .custom_formatter(|n, _| midi_name(n as u8)),
+                    )
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Routing:");
+                for (routing, label) in [
+                    (RoutingUi::Serial, "Serial (1 → 2)"),
+                    (RoutingUi::Parallel, "Parallel"),
+                    (RoutingUi::Split, "Split"),
+                ] {
+                    changed.3 |= ui
+                        .selectable_value(&mut self.filter.routing, routing, label)
+                        .changed();
+                }
+            });
+            if self.filter.routing != RoutingUi::Serial {
                 ui.horizontal(|ui| {
-                    ui.label("Cutoff:");
+                    ui.label("Balance:");
                     changed.3 |= ui
                         .add(
-                            egui::Slider::new(&mut self.filter.cutoff, 20.0..=20000.0)
-                                .text("Cutoff"),
+                            egui::Slider::new(&mut self.filter.balance, 0.0..=1.0)
+                                .custom_formatter(|v, _| match v {
+                                    v if v < 0.5 => format!("1 / {:.0}%", 200.0 * v),
+                                    v if v > 0.5 => format!("{:.0}% / 2", 200.0 * (1.0 - v)),
+                                    _ => "1 = 2".to_owned(),
+                                }),
                         )
                         .changed();
                 });
-                if self.filter.filter_type == FilterTypeUi::TwoPoleLpf {
+            }
+            if self.filter.routing == RoutingUi::Split {
+                for (label, slot) in [
+                    ("Main osc →", &mut self.filter.main_slot),
+                    ("Sub osc →", &mut self.filter.sub_slot),
+                ] {
                     ui.horizontal(|ui| {
-                        ui.label("Resonance (Q):");
+                        ui.label(label);
+                        changed.3 |= ui
+                            .selectable_value(slot, FilterSlot::One, "Filter 1")
+                            .changed();
                         changed.3 |= ui
-                            .add(egui::Slider::new(&mut self.filter.q, 0.1..=10.0).text("Q"))
+                            .selectable_value(slot, FilterSlot::Two, "Filter 2")
                             .changed();
                     });
                 }
-            };
+            }
+            if self.filter.slots.iter().any(|f| f.show) {
+                let sr = self.bus.sample_rate();
+                let filter = self.filter.dual_filter(sr);
+                response_plot(ui, &filter, self.filter.routing(), sr);
+            }
+            let has_formant = (self.filter.slots.iter())
+                .any(|f| f.show && f.filter_type == FilterTypeUi::Formant);
+            if has_formant {
+                let m = &mut self.filter.vowel_mod;
+                ui.horizontal(|ui| {
+                    ui.label("Vowel LFO:");
+                    changed.3 |= ui
+                        .add(egui::Slider::new(&mut m.lfo_rate, 0.0..=10.0).suffix(" Hz"))
+                        .changed();
+                    changed.3 |= ui
+                        .add(egui::Slider::new(&mut m.lfo_depth, 0.0..=2.0).text("Depth"))
+                        .changed();
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Vowel env:");
+                    changed.3 |= ui
+                        .add(egui::Slider::new(&mut m.env_depth, -4.0..=4.0).text("Depth"))
+                        .changed();
+                });
+            }
 
             if changed.0 {
-                let _ = self.bus.q.push(Msg::SetMasterVolume(self.master));
+                let _ = self.bus.send(Msg::SetMasterVolume(self.master));
             }
             if changed.1 {
-                let _ = self.bus.q.push(Msg::SetAdsr {
+                let _ = self.bus.send(Msg::SetAdsr {
                     a: self.attack,
                     d: self.decay,
                     s: self.sustain,
@@ -243,16 +1250,50 @@ impl App for EguiUi {
             if changed.2 {
                 let _ = self
                     .bus
-                    .q
-                    .push(Msg::SetWaveform(self.waveform.clone().into()));
+                    .send(Msg::SetWaveform(self.waveform.clone().into()));
             }
-            if changed.3 {
-                let filter_msg = if self.filter.show {
-                    Some(self.filter.clone().into())
+            // ドラッグ中は毎フレーム作り直すと重いので間引き、最後の状態は必ず送る
+            self.wavetable_dirty |= changed.6;
+            if self.wavetable_dirty {
+                let now = ctx.input(|i| i.time);
+                let wait = self.wavetable_sent + WAVETABLE_INTERVAL - now;
+                if wait <= 0.0 {
+                    self.push_wavetable();
+                    self.wavetable_dirty = false;
+                    self.wavetable_sent = now;
                 } else {
-                    None
-                };
-                let _ = self.bus.q.push(Msg::SetFilter(filter_msg));
+                    ctx.request_repaint_after(Duration::from_secs_f64(wait));
+                }
+            }
+            if changed.7 {
+                let _ = self.bus.send(Msg::SetVoiceType(self.voice.clone().into()));
+            }
+            if changed.3 {
+                let _ = self.bus.send(Msg::SetFilterKeyTrack {
+                    amount: self.filter.key_track,
+                    center: self.filter.key_center,
+                });
+                for (slot, ui) in [FilterSlot::One, FilterSlot::Two]
+                    .into_iter()
+                    .zip(self.filter.slots.iter())
+                {
+                    let filter = ui.show.then(|| ui.clone().into());
+                    let _ = self.bus.send(Msg::SetFilter(slot, filter));
+                }
+                let _ = self.bus.send(Msg::SetFilterRouting(self.filter.routing()));
+                let _ = self.bus.send(Msg::SetVowelMod(self.filter.vowel_mod));
+            }
+            if changed.4 {
+                let _ = self.bus.send(Msg::SetSubOsc(self.sub.clone().into()));
+            }
+            if changed.5 {
+                let _ = self.bus.send(Msg::SetPhaseMode(self.phase_mode));
+            }
+            if changed.8 {
+                let _ = self.bus.send(Msg::SetStereo(self.stereo));
+            }
+            if changed.9 {
+                let _ = self.bus.send(Msg::SetLimiter(self.limiter));
             }
         });
 
@@ -278,9 +1319,12 @@ impl App for EguiUi {
                     continue;
                 }
                 let _ = if pressed {
-                    self.bus.q.push(Msg::NoteOn { note })
+                    self.bus.send(Msg::NoteOn {
+                        note,
+                        velocity: self.velocity,
+                    })
                 } else {
-                    self.bus.q.push(Msg::NoteOff { note })
+                    self.bus.send(Msg::NoteOff { note })
                 };
             }
         }
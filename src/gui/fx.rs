@@ -0,0 +1,532 @@
+use crate::synth::{
+    ChorusParams, CrusherParams, DelayParams, DelayTime, DistortionParams, Effect, Feel,
+    FlangerParams, FxType, MAX_CHORUS_VOICES, MAX_DELAY_SEC, MAX_FX, MAX_PRE_DELAY_MS, MAX_STAGES,
+    Msg, NoteDiv, Oversample, PhaserParams, ReverbParams, ShapeCurve, SharedBus,
+};
+use eframe::egui;
+
+#[derive(Clone, Copy, PartialEq)]
+enum FxKindUi {
+    None,
+    Gain,
+    Delay,
+    Reverb,
+    Chorus,
+    Flanger,
+    Phaser,
+    Distortion,
+    Crusher,
+}
+
+impl FxKindUi {
+    const ALL: [(FxKindUi, &'static str); 9] = [
+        (Self::None, "None"),
+        (Self::Gain, "Gain"),
+        (Self::Delay, "Delay"),
+        (Self::Reverb, "Reverb"),
+        (Self::Chorus, "Chorus"),
+        (Self::Flanger, "Flanger"),
+        (Self::Phaser, "Phaser"),
+        (Self::Distortion, "Distortion"),
+        (Self::Crusher, "Bitcrusher"),
+    ];
+
+    fn label(self) -> &'static str {
+        Self::ALL
+            .iter()
+            .find(|(k, _)| *k == self)
+            .map_or("", |(_, label)| label)
+    }
+
+    /// 種類を選んだときの初期の Mix
+    fn default_mix(self) -> f32 {
+        match self {
+            Self::Delay => 0.35,
+            Self::Reverb => 0.3,
+            Self::Chorus | Self::Flanger => 0.5,
+            Self::None | Self::Gain | Self::Phaser | Self::Distortion | Self::Crusher => 1.0,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct FxSlotUi {
+    kind: FxKindUi,
+    mix: f32,
+    bypass: bool,
+    gain_db: f32,
+    delay: DelayParams,
+    delay_ms: f32, // ms / 音符の切り替えで値を覚えておく
+    delay_div: NoteDiv,
+    reverb: ReverbParams,
+    chorus: ChorusParams,
+    flanger: FlangerParams,
+    phaser: PhaserParams,
+    distortion: DistortionParams,
+    crusher: CrusherParams,
+}
+
+impl Default for FxSlotUi {
+    fn default() -> Self {
+        Self {
+            kind: FxKindUi::None,
+            mix: 1.0,
+            bypass: false,
+            gain_db: 0.0,
+            delay: DelayParams::default(),
+            delay_ms: 300.0,
+            delay_div: NoteDiv {
+                denom: 8,
+                feel: Feel::Dotted,
+            },
+            reverb: ReverbParams::default(),
+            chorus: ChorusParams::default(),
+            flanger: FlangerParams::default(),
+            phaser: PhaserParams::default(),
+            distortion: DistortionParams::default(),
+            crusher: CrusherParams::default(),
+        }
+    }
+}
+
+impl FxSlotUi {
+    fn fx_type(&self) -> Option<FxType> {
+        match self.kind {
+            FxKindUi::None => None,
+            FxKindUi::Gain => Some(FxType::Gain(self.gain_db)),
+            FxKindUi::Delay => Some(FxType::Delay(self.delay)),
+            FxKindUi::Reverb => Some(FxType::Reverb(self.reverb)),
+            FxKindUi::Chorus => Some(FxType::Chorus(self.chorus)),
+            FxKindUi::Flanger => Some(FxType::Flanger(self.flanger)),
+            FxKindUi::Phaser => Some(FxType::Phaser(self.phaser)),
+            FxKindUi::Distortion => Some(FxType::Distortion(self.distortion)),
+            FxKindUi::Crusher => Some(FxType::Crusher(self.crusher)),
+        }
+    }
+
+    /// 種類ごとのパラメータUI（変更があれば true）
+    fn params_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        match self.kind {
+            FxKindUi::None => false,
+            FxKindUi::Gain => ui
+                .add(
+                    egui::Slider::new(&mut self.gain_db, -24.0..=24.0)
+                        .text("Gain")
+                        .suffix(" dB"),
+                )
+                .changed(),
+            FxKindUi::Delay => delay_ui(ui, self),
+            FxKindUi::Reverb => reverb_ui(ui, &mut self.reverb),
+            FxKindUi::Chorus => chorus_ui(ui, &mut self.chorus),
+            FxKindUi::Flanger => flanger_ui(ui, &mut self.flanger),
+            FxKindUi::Phaser => phaser_ui(ui, &mut self.phaser),
+            FxKindUi::Distortion => distortion_ui(ui, &mut self.distortion),
+            FxKindUi::Crusher => crusher_ui(ui, &mut self.crusher),
+        }
+    }
+}
+
+/// LFO の速さ・深さ・左右のずれ（モジュレーション系で共通）
+fn lfo_ui(
+    ui: &mut egui::Ui,
+    rate: &mut f32,
+    depth: &mut f32,
+    depth_range: std::ops::RangeInclusive<f32>,
+    depth_suffix: &str,
+    spread: &mut f32,
+) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(rate, 0.01..=10.0)
+                    .logarithmic(true)
+                    .text("Rate")
+                    .suffix(" Hz"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(depth, depth_range)
+                    .text("Depth")
+                    .suffix(depth_suffix),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(spread, 0.0..=1.0).text("Stereo"))
+            .changed();
+    });
+    changed
+}
+
+fn chorus_ui(ui: &mut egui::Ui, p: &mut ChorusParams) -> bool {
+    let mut changed = lfo_ui(
+        ui,
+        &mut p.rate,
+        &mut p.depth,
+        0.0..=20.0,
+        " ms",
+        &mut p.spread,
+    );
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(egui::Slider::new(&mut p.voices, 1..=MAX_CHORUS_VOICES).text("Voices"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.delay, 1.0..=40.0)
+                    .text("Delay")
+                    .suffix(" ms"),
+            )
+            .changed();
+    });
+    changed
+}
+
+fn flanger_ui(ui: &mut egui::Ui, p: &mut FlangerParams) -> bool {
+    let mut changed = lfo_ui(
+        ui,
+        &mut p.rate,
+        &mut p.depth,
+        0.0..=10.0,
+        " ms",
+        &mut p.spread,
+    );
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.delay, 0.1..=10.0)
+                    .text("Delay")
+                    .suffix(" ms"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut p.feedback, -0.95..=0.95).text("Feedback"))
+            .changed();
+    });
+    changed
+}
+
+fn phaser_ui(ui: &mut egui::Ui, p: &mut PhaserParams) -> bool {
+    let mut changed = lfo_ui(
+        ui,
+        &mut p.rate,
+        &mut p.depth,
+        0.0..=6.0,
+        " oct",
+        &mut p.spread,
+    );
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.center, 100.0..=5000.0)
+                    .logarithmic(true)
+                    .text("Center")
+                    .suffix(" Hz"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut p.feedback, -0.95..=0.95).text("Feedback"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.stages, 2..=MAX_STAGES)
+                    .step_by(2.0)
+                    .text("Stages"),
+            )
+            .changed();
+    });
+    changed
+}
+
+fn distortion_ui(ui: &mut egui::Ui, p: &mut DistortionParams) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Curve:");
+        for (curve, label) in [
+            (ShapeCurve::Tanh, "Tanh"),
+            (ShapeCurve::HardClip, "Hard clip"),
+            (ShapeCurve::Foldback, "Foldback"),
+            (ShapeCurve::Tube, "Tube"),
+        ] {
+            changed |= ui.selectable_value(&mut p.curve, curve, label).changed();
+        }
+        ui.separator();
+        ui.label("Oversample:");
+        for (os, label) in [
+            (Oversample::X1, "1x"),
+            (Oversample::X2, "2x"),
+            (Oversample::X4, "4x"),
+        ] {
+            changed |= ui.selectable_value(&mut p.oversample, os, label).changed();
+        }
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.drive, 0.0..=48.0)
+                    .text("Drive")
+                    .suffix(" dB"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.output, -36.0..=12.0)
+                    .text("Output")
+                    .suffix(" dB"),
+            )
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.low_cut, 20.0..=2000.0)
+                    .logarithmic(true)
+                    .text("Low cut")
+                    .suffix(" Hz"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.tone, 500.0..=20000.0)
+                    .logarithmic(true)
+                    .text("Tone")
+                    .suffix(" Hz"),
+            )
+            .changed();
+    });
+    changed
+}
+
+fn crusher_ui(ui: &mut egui::Ui, p: &mut CrusherParams) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(egui::Slider::new(&mut p.bits, 1.0..=16.0).text("Bits"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.rate, 100.0..=48000.0)
+                    .logarithmic(true)
+                    .text("Rate")
+                    .suffix(" Hz"),
+            )
+            .changed();
+    });
+    changed
+}
+
+fn reverb_ui(ui: &mut egui::Ui, p: &mut ReverbParams) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(egui::Slider::new(&mut p.size, 0.0..=1.0).text("Size"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.decay, 0.1..=20.0)
+                    .logarithmic(true)
+                    .text("Decay")
+                    .suffix(" s"),
+            )
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(egui::Slider::new(&mut p.damping, 0.0..=1.0).text("Damping"))
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.pre_delay, 0.0..=MAX_PRE_DELAY_MS)
+                    .text("Pre-delay")
+                    .suffix(" ms"),
+            )
+            .changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut p.width, 0.0..=1.0).text("Width"))
+            .changed();
+    });
+    changed
+}
+
+fn delay_ui(ui: &mut egui::Ui, slot: &mut FxSlotUi) -> bool {
+    let mut changed = false;
+    let mut sync = matches!(slot.delay.time, DelayTime::Sync(_));
+    ui.horizontal(|ui| {
+        ui.label("Time:");
+        changed |= ui.selectable_value(&mut sync, false, "ms").changed();
+        changed |= ui.selectable_value(&mut sync, true, "Sync").changed();
+        if sync {
+            let div = &mut slot.delay_div;
+            egui::ComboBox::from_id_salt("div")
+                .selected_text(format!("1/{}", div.denom))
+                .show_ui(ui, |ui| {
+                    for denom in [1, 2, 4, 8, 16, 32] {
+                        changed |= ui
+                            .selectable_value(&mut div.denom, denom, format!("1/{denom}"))
+                            .changed();
+                    }
+                });
+            for (feel, label) in [
+                (Feel::Straight, "Straight"),
+                (Feel::Dotted, "Dotted"),
+                (Feel::Triplet, "Triplet"),
+            ] {
+                changed |= ui.selectable_value(&mut div.feel, feel, label).changed();
+            }
+        } else {
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut slot.delay_ms, 1.0..=MAX_DELAY_SEC * 1000.0)
+                        .logarithmic(true)
+                        .suffix(" ms"),
+                )
+                .changed();
+        }
+    });
+    slot.delay.time = if sync {
+        DelayTime::Sync(slot.delay_div)
+    } else {
+        DelayTime::Ms(slot.delay_ms)
+    };
+    let p = &mut slot.delay;
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(egui::Slider::new(&mut p.feedback, 0.0..=0.95).text("Feedback"))
+            .changed();
+        changed |= ui.checkbox(&mut p.ping_pong, "Ping-pong").changed();
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.low_cut, 20.0..=2000.0)
+                    .logarithmic(true)
+                    .text("Low cut")
+                    .suffix(" Hz"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.high_cut, 1000.0..=20000.0)
+                    .logarithmic(true)
+                    .text("High cut")
+                    .suffix(" Hz"),
+            )
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.mod_rate, 0.05..=5.0)
+                    .logarithmic(true)
+                    .text("Mod rate")
+                    .suffix(" Hz"),
+            )
+            .changed();
+        changed |= ui
+            .add(
+                egui::Slider::new(&mut p.mod_depth, 0.0..=20.0)
+                    .text("Depth")
+                    .suffix(" ms"),
+            )
+            .changed();
+    });
+    changed
+}
+
+/// ボイスのミックスの後ろに挿すエフェクトチェーンの設定
+pub struct FxRackUi {
+    slots: [FxSlotUi; MAX_FX],
+    order: [u8; MAX_FX], // 上から処理する順のスロット番号
+    bpm: f32,
+}
+
+impl Default for FxRackUi {
+    fn default() -> Self {
+        Self {
+            slots: Default::default(),
+            order: std::array::from_fn(|i| i as u8),
+            bpm: 120.0,
+        }
+    }
+}
+
+impl FxRackUi {
+    /// チェーンの設定UI（変更はその場でバスへ送る）
+    pub fn show(&mut self, ui: &mut egui::Ui, bus: &SharedBus) {
+        ui.horizontal(|ui| {
+            ui.label("Effects:");
+            if ui
+                .add(
+                    egui::Slider::new(&mut self.bpm, 40.0..=240.0)
+                        .text("Tempo")
+                        .suffix(" BPM"),
+                )
+                .changed()
+            {
+                let _ = bus.send(Msg::SetTempo(self.bpm));
+            }
+        });
+        let mut swap = None;
+        for pos in 0..MAX_FX {
+            let idx = self.order[pos] as usize;
+            let slot = &mut self.slots[idx];
+            let (mut kind_changed, mut params_changed) = (false, false);
+            let (mut mix_changed, mut bypass_changed) = (false, false);
+            ui.push_id(idx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}.", pos + 1));
+                    if ui.add_enabled(pos > 0, egui::Button::new("▲")).clicked() {
+                        swap = Some(pos - 1);
+                    }
+                    if ui
+                        .add_enabled(pos + 1 < MAX_FX, egui::Button::new("▼"))
+                        .clicked()
+                    {
+                        swap = Some(pos);
+                    }
+                    egui::ComboBox::from_id_salt("kind")
+                        .selected_text(slot.kind.label())
+                        .show_ui(ui, |ui| {
+                            for (kind, label) in FxKindUi::ALL {
+                                kind_changed |=
+                                    ui.selectable_value(&mut slot.kind, kind, label).changed();
+                            }
+                        });
+                    if slot.kind != FxKindUi::None {
+                        bypass_changed |= ui.checkbox(&mut slot.bypass, "Bypass").changed();
+                        mix_changed |= ui
+                            .add(egui::Slider::new(&mut slot.mix, 0.0..=1.0).text("Mix"))
+                            .changed();
+                    }
+                });
+                if slot.kind != FxKindUi::None && !slot.bypass {
+                    ui.indent("params", |ui| {
+                        params_changed |= slot.params_ui(ui);
+                    });
+                }
+            });
+
+            if kind_changed && idx < MAX_FX {
+                slot.mix = slot.kind.default_mix();
+                mix_changed = true;
+                // バッファの確保はここ（UI スレッド）で済ませてから送る
+                let sr = bus.sample_rate();
+                let fx = slot.fx_type().map(|ft| Box::new(Effect::new(ft, sr)));
+                let _ = bus.send(Msg::LoadFx(idx, fx));
+            } else if params_changed && let Some(ft) = slot.fx_type() {
+                let _ = bus.send(Msg::SetFx(idx, ft));
+            }
+            if mix_changed {
+                let _ = bus.send(Msg::SetFxMix(idx, slot.mix));
+            }
+            if bypass_changed {
+                let _ = bus.send(Msg::SetFxBypass(idx, slot.bypass));
+            }
+        }
+        if let Some(pos) = swap {
+            self.order.swap(pos, pos + 1);
+            let _ = bus.send(Msg::SetFxOrder(self.order));
+        }
+    }
+}
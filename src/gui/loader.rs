@@ -0,0 +1,138 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, Sender},
+    },
+};
+
+use crate::synth::{Instrument, Sample};
+use eframe::egui;
+
+/// 読み込みが終わった WAV / SFZ（エラーは表示用の文字列）
+pub enum Loaded {
+    Sample {
+        name: String,
+        result: Result<Sample, String>,
+    },
+    Instrument {
+        name: String,
+        result: Result<Instrument, String>,
+    },
+}
+
+/// 読み込むファイルの中身（ドロップされたバイト列 or ネイティブのパス）
+#[derive(Clone)]
+pub enum Source {
+    Bytes(Arc<[u8]>),
+    Path(PathBuf),
+}
+
+impl Source {
+    fn read(&self) -> Result<Vec<u8>, String> {
+        match self {
+            Source::Bytes(bytes) => Ok(bytes.to_vec()),
+            Source::Path(path) => std::fs::read(path).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() -> Loaded + Send>;
+
+/// ファイルの読み出しとデコードを UI スレッドの外で行う
+/// ジョブは1本のワーカーで順に処理するので、結果は頼んだ順に届く
+/// （wasm ではスレッドがないのでその場で処理する）
+pub struct Loader {
+    #[cfg(not(target_arch = "wasm32"))]
+    jobs: Sender<(Job, egui::Context)>,
+    #[cfg(target_arch = "wasm32")]
+    done_tx: Sender<Loaded>,
+    done_rx: Receiver<Loaded>,
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        let (done_tx, done_rx) = mpsc::channel();
+        #[cfg(not(target_arch = "wasm32"))]
+        let jobs = {
+            let (jobs, rx) = mpsc::channel::<(Job, egui::Context)>();
+            std::thread::spawn(move || {
+                for (job, ctx) in rx {
+                    if done_tx.send(job()).is_err() {
+                        break;
+                    }
+                    ctx.request_repaint();
+                }
+            });
+            jobs
+        };
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            jobs,
+            #[cfg(target_arch = "wasm32")]
+            done_tx,
+            done_rx,
+        }
+    }
+}
+
+impl Loader {
+    /// WAV を読み込む
+    pub fn sample(&self, ctx: &egui::Context, name: String, src: Source) {
+        self.spawn(ctx, move || Loaded::Sample {
+            result: src
+                .read()
+                .and_then(|bytes| Sample::from_wav(&bytes).map_err(|e| e.to_string())),
+            name,
+        });
+    }
+
+    /// SFZ を読み込む
+    /// サンプルは一緒に渡されたファイル（ファイル名で照合）か、dir からの相対パスで探す
+    pub fn instrument(
+        &self,
+        ctx: &egui::Context,
+        name: String,
+        src: Source,
+        dir: PathBuf,
+        others: Vec<(String, Source)>,
+    ) {
+        self.spawn(ctx, move || Loaded::Instrument {
+            result: src.read().and_then(|bytes| {
+                let text = String::from_utf8_lossy(&bytes);
+                Instrument::from_sfz(&text, |path| {
+                    let bytes = find_sample(path, &dir, &others)?;
+                    Sample::from_wav(&bytes).map_err(|e| e.to_string())
+                })
+                .map_err(|e| e.to_string())
+            }),
+            name,
+        });
+    }
+
+    /// 終わった読み込みを1つ取り出す
+    pub fn poll(&self) -> Option<Loaded> {
+        self.done_rx.try_recv().ok()
+    }
+
+    fn spawn(&self, ctx: &egui::Context, job: impl FnOnce() -> Loaded + Send + 'static) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = self.jobs.send((Box::new(job), ctx.clone()));
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = self.done_tx.send(job());
+            ctx.request_repaint();
+        }
+    }
+}
+
+fn find_sample(path: &str, dir: &Path, others: &[(String, Source)]) -> Result<Vec<u8>, String> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    match others
+        .iter()
+        .find(|(n, _)| n.replace('\\', "/").rsplit('/').next() == Some(file_name))
+    {
+        Some((_, src)) => src.read(),
+        None => std::fs::read(dir.join(path)).map_err(|e| e.to_string()),
+    }
+}
@@ -0,0 +1,129 @@
+use crate::synth::{CompressorParams, EQ_BANDS, EqParams, Msg, SharedBus};
+use eframe::egui;
+
+const BAND_LABELS: [&str; EQ_BANDS] = ["Low", "Mid 1", "Mid 2", "High"];
+
+/// マスターの EQ とコンプレッサーの設定
+#[derive(Default)]
+pub struct MasterBusUi {
+    eq: EqParams,
+    compressor: CompressorParams,
+    gain_reduction_db: f32, // 表示用（ゆっくり戻す）
+}
+
+impl MasterBusUi {
+    /// EQ とコンプレッサーの設定UI（変更はその場でバスへ送る）
+    pub fn show(&mut self, ui: &mut egui::Ui, bus: &SharedBus) {
+        if self.eq_ui(ui) {
+            let _ = bus.send(Msg::SetEq(self.eq));
+        }
+        if self.compressor_ui(ui, bus) {
+            let _ = bus.send(Msg::SetCompressor(self.compressor));
+        }
+    }
+
+    fn eq_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = ui.checkbox(&mut self.eq.enabled, "EQ").changed();
+        if !self.eq.enabled {
+            return changed;
+        }
+        for (i, band) in self.eq.bands.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(BAND_LABELS[i]);
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut band.freq, 20.0..=20000.0)
+                                .logarithmic(true)
+                                .text("Freq")
+                                .suffix(" Hz"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut band.gain_db, -18.0..=18.0)
+                                .text("Gain")
+                                .suffix(" dB"),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::Slider::new(&mut band.q, 0.1..=10.0)
+                                .logarithmic(true)
+                                .text("Q"),
+                        )
+                        .changed();
+                });
+            });
+        }
+        changed
+    }
+
+    fn compressor_ui(&mut self, ui: &mut egui::Ui, bus: &SharedBus) -> bool {
+        let p = &mut self.compressor;
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut p.enabled, "Compressor").changed();
+            let gr = bus.take_compressor_gain();
+            let db = -20.0 * gr.max(1.0e-6).log10();
+            self.gain_reduction_db = db.max(self.gain_reduction_db - 0.5);
+            ui.add(
+                egui::ProgressBar::new(self.gain_reduction_db / 24.0)
+                    .desired_width(120.0)
+                    .text(format!("GR {:.1} dB", -self.gain_reduction_db)),
+            );
+        });
+        if !p.enabled {
+            return changed;
+        }
+        ui.horizontal(|ui| {
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut p.threshold_db, -60.0..=0.0)
+                        .text("Threshold")
+                        .suffix(" dB"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut p.ratio, 1.0..=20.0)
+                        .logarithmic(true)
+                        .text("Ratio"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut p.knee_db, 0.0..=24.0)
+                        .text("Knee")
+                        .suffix(" dB"),
+                )
+                .changed();
+        });
+        ui.horizontal(|ui| {
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut p.attack_ms, 0.1..=200.0)
+                        .logarithmic(true)
+                        .text("Attack")
+                        .suffix(" ms"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut p.release_ms, 10.0..=2000.0)
+                        .logarithmic(true)
+                        .text("Release")
+                        .suffix(" ms"),
+                )
+                .changed();
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut p.makeup_db, 0.0..=24.0)
+                        .text("Makeup")
+                        .suffix(" dB"),
+                )
+                .changed();
+        });
+        changed
+    }
+}
@@ -0,0 +1,128 @@
+use crate::synth::{EnvState, MAX_VOICES, SharedBus, Telemetry};
+use eframe::egui;
+
+const METER_FLOOR_DB: f32 = -60.0;
+const PEAK_FALL_DB: f32 = 1.0; // 1 フレームあたりのピーク表示の落ち方
+const LOW_KEY: u8 = 48; // C3（Note の範囲）
+const HIGH_KEY: u8 = 83; // B5
+const BLACK_KEYS: [bool; 12] = [
+    false, true, false, true, false, false, true, false, true, false, true, false,
+];
+
+/// オーディオ側から届いた様子（レベル・CPU・鳴っている鍵盤）の表示
+pub struct MetersUi {
+    latest: Telemetry,
+    peak_db: [f32; 2], // 表示用（ゆっくり落とす）
+}
+
+impl Default for MetersUi {
+    fn default() -> Self {
+        Self {
+            latest: Telemetry::default(),
+            peak_db: [METER_FLOOR_DB; 2],
+        }
+    }
+}
+
+fn to_db(x: f32) -> f32 {
+    (20.0 * x.max(1.0e-6).log10()).max(METER_FLOOR_DB)
+}
+
+impl MetersUi {
+    pub fn show(&mut self, ui: &mut egui::Ui, bus: &SharedBus) {
+        if let Some(t) = bus.take_telemetry() {
+            self.latest = t;
+        }
+        let t = self.latest;
+        for ch in 0..2 {
+            self.peak_db[ch] = to_db(t.peak[ch]).max(self.peak_db[ch] - PEAK_FALL_DB);
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(format!("Voices: {}/{MAX_VOICES}", t.active_voices));
+            ui.separator();
+            ui.add(
+                egui::ProgressBar::new(t.cpu.clamp(0.0, 1.0))
+                    .desired_width(120.0)
+                    .text(format!("CPU {:.0}%", t.cpu * 100.0)),
+            );
+            ui.separator();
+            ui.label(format!("Dropped: {}", t.dropped));
+        });
+        for (ch, name) in ["L", "R"].into_iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(name);
+                level_bar(ui, to_db(t.rms[ch]), self.peak_db[ch]);
+                ui.label(format!("{:.1} dB", self.peak_db[ch]));
+            });
+        }
+        keyboard(ui, &t);
+    }
+}
+
+/// RMS を塗りつぶし、ピークを縦線で描く
+fn level_bar(ui: &mut egui::Ui, rms_db: f32, peak_db: f32) {
+    let size = egui::vec2(240.0, 10.0);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+    let x_of = |db: f32| rect.left() + (1.0 - db / METER_FLOOR_DB) * rect.width();
+    let fill = egui::Rect::from_min_max(rect.left_top(), egui::pos2(x_of(rms_db), rect.bottom()));
+    painter.rect_filled(fill, 0.0, ui.visuals().selection.bg_fill);
+    let color = if peak_db >= -0.5 {
+        egui::Color32::RED
+    } else {
+        ui.visuals().strong_text_color()
+    };
+    painter.vline(x_of(peak_db), rect.y_range(), egui::Stroke::new(2.0, color));
+}
+
+/// 鳴っている鍵盤をエンベロープの大きさで塗る
+fn keyboard(ui: &mut egui::Ui, t: &Telemetry) {
+    let mut levels = [0.0f32; (HIGH_KEY - LOW_KEY + 1) as usize];
+    for v in t.voices.iter().filter(|v| v.state != EnvState::Idle) {
+        if let Some(key) = v.note.midi().filter(|&m| (LOW_KEY..=HIGH_KEY).contains(&m)) {
+            let l = &mut levels[(key - LOW_KEY) as usize];
+            *l = l.max(v.level);
+        }
+    }
+
+    let whites = (LOW_KEY..=HIGH_KEY)
+        .filter(|k| !BLACK_KEYS[*k as usize % 12])
+        .count();
+    let size = egui::vec2(ui.available_width().min(360.0), 40.0);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let w = rect.width() / whites as f32;
+    let active = ui.visuals().selection.bg_fill;
+    let tint = |base: egui::Color32, level: f32| {
+        if level > 0.0 {
+            base.lerp_to_gamma(active, 0.3 + 0.7 * level.clamp(0.0, 1.0))
+        } else {
+            base
+        }
+    };
+    let stroke = egui::Stroke::new(1.0, egui::Color32::DARK_GRAY);
+
+    // 白鍵を先に、その上に黒鍵を描く
+    let mut x = rect.left();
+    let mut black = Vec::new();
+    for key in LOW_KEY..=HIGH_KEY {
+        let level = levels[(key - LOW_KEY) as usize];
+        if BLACK_KEYS[key as usize % 12] {
+            black.push((x, level));
+            continue;
+        }
+        let r = egui::Rect::from_min_size(egui::pos2(x, rect.top()), egui::vec2(w, rect.height()));
+        painter.rect_filled(r, 0.0, tint(egui::Color32::WHITE, level));
+        painter.rect_stroke(r, 0.0, stroke, egui::StrokeKind::Inside);
+        x += w;
+    }
+    for (x, level) in black {
+        let r = egui::Rect::from_center_size(
+            egui::pos2(x, rect.top() + rect.height() * 0.3),
+            egui::vec2(w * 0.6, rect.height() * 0.6),
+        );
+        painter.rect_filled(r, 0.0, tint(egui::Color32::BLACK, level));
+    }
+}
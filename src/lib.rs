@@ -1,17 +1,51 @@
 pub mod synth {
+    mod additive;
     mod adsr;
+    mod compressor;
     mod engine;
+    mod eq;
     mod filter;
+    mod fx;
+    mod granular;
+    mod instrument;
+    mod limiter;
     mod note;
     mod osc;
+    mod pluck;
+    mod rng;
+    mod sample;
     mod shared_bus;
+    mod smooth;
+    mod stereo;
+    mod telemetry;
     // Re-export primary types to avoid deep paths
-    pub use engine::Synth;
-    pub use filter::FilterType;
-    pub use note::Note;
-    pub use osc::Waveform;
-    pub use shared_bus::Msg;
+    pub use additive::{MAX_HARMONICS, Spectrum, Wavetable};
+    pub use adsr::EnvState;
+    pub use compressor::CompressorParams;
+    pub use engine::{MAX_VOICES, Synth, VoiceType};
+    pub use eq::{EQ_BANDS, EqBand, EqParams};
+    pub use filter::{
+        Comb, CombLine, CombMode, Complex, DualFilter, Filter, FilterRouting, FilterSlot,
+        FilterTrait, FilterType, Formant, Ladder, MAX_CUTOFF, MAX_STAGES, MIN_CUTOFF, Phaser, Svf,
+        SvfOutputs, VowelMod, VowelSet,
+    };
+    pub use fx::{
+        ChorusParams, CrusherParams, DelayParams, DelayTime, DistortionParams, Effect, EffectTrait,
+        Feel, FlangerParams, FxType, MAX_CHORUS_VOICES, MAX_DELAY_SEC, MAX_FX, MAX_PRE_DELAY_MS,
+        NoteDiv, Oversample, PhaserParams, ReverbParams, ShapeCurve,
+    };
+    pub use granular::GrainParams;
+    pub use instrument::{Instrument, Region, SfzError};
+    pub use limiter::{ClipMode, LimiterParams};
+    pub use note::{Note, hz_name, hz_to_midi, midi_name, midi_to_hz, parse_note_name};
+    pub use osc::{PhaseMode, SubOsc, SubShape, Waveform};
+    pub use pluck::PluckParams;
+    pub use sample::{LoopMode, Sample, SampleParams, WavError};
+    pub use shared_bus::{Event, Msg};
+    pub use shared_bus::Retired;
     pub use shared_bus::SharedBus;
+    pub use stereo::{SpreadMode, Stereo};
+    pub use telemetry::{LevelMeter, Telemetry, VoiceInfo};
 }
 
 pub mod audio {
@@ -20,6 +54,10 @@ pub mod audio {
 
 pub mod gui {
     mod app;
+    mod fx;
+    mod loader;
+    mod master;
+    mod meters;
     pub use app::EguiUi;
 }
 
@@ -27,7 +65,7 @@ pub mod gui {
 #[cfg(target_arch = "wasm32")]
 pub(crate) mod web_entry {
     use crate::gui::EguiUi;
-    use crate::synth::{Msg, SharedBus, Synth, Waveform};
+    use crate::synth::{SharedBus, Synth, Waveform};
     use crate::audio::core::{render_block, QUANTUM};
     use eframe::{App, WebOptions, WebRunner};
     use wasm_bindgen::JsCast;
@@ -41,8 +79,32 @@ pub(crate) mod web_entry {
         static WORKLET_NODE: RefCell<Option<web_sys::AudioWorkletNode>> = const { RefCell::new(None) };
     }
 
+    /// Render `total` frames and post them to the worklet as planar `left` / `right` arrays.
+    fn render_and_post(
+        synth: &mut Synth,
+        bus: &SharedBus,
+        port: &web_sys::MessagePort,
+        total: usize,
+    ) {
+        use web_sys::js_sys::{Array, Float32Array, Object, Reflect};
+
+        let mut left = vec![0.0f32; total];
+        let mut right = vec![0.0f32; total];
+        // Render in one go so queued messages are spread by timestamp across the whole buffer
+        render_block(synth, bus, &mut left, &mut right);
+        let payload = Object::new();
+        let transfer = Array::new();
+        for (key, data) in [("left", &left), ("right", &right)] {
+            let arr = Float32Array::new_with_length(total as u32);
+            arr.copy_from(data);
+            transfer.push(&arr.buffer());
+            let _ = Reflect::set(&payload, &JsValue::from_str(key), &arr);
+        }
+        let _ = port.post_message_with_transferable(&payload, &transfer);
+    }
+
     async fn init_audio(bus: SharedBus) -> Result<(), JsValue> {
-        use web_sys::js_sys::{Array, ArrayBuffer, Float32Array, Object, Reflect};
+        use web_sys::js_sys::{Array, Reflect};
 
         // Prefer interactive/low-latency context if available
         let ctx = if true {
@@ -77,56 +139,20 @@ pub(crate) mod web_entry {
 
         // Prepare synth and message handler
         let mut synth = Synth::new(sr, Waveform::Sine, None);
+        bus.set_sample_rate(sr);
         let bus_for_cb = bus.clone();
 
         let port_for_cb = port.clone();
         // Pre-fill one contiguous buffer of target blocks to reduce startup glitch
-        {
-            let total = QUANTUM * 8; // target blocks (keep in sync with worklet)
-            let mut mono = vec![0.0f32; total];
-            let mut idx = 0;
-            while idx < total {
-                let end = (idx + QUANTUM).min(total);
-                render_block(&mut synth, &bus_for_cb, &mut mono[idx..end]);
-                idx = end;
-            }
-            let arr = Float32Array::new_with_length(total as u32);
-            arr.copy_from(&mono);
-            let buf: ArrayBuffer = arr.buffer();
-            let payload = Object::new();
-            let _ = Reflect::set(&payload, &JsValue::from_str("mono"), &arr);
-            let _ = port.post_message_with_transferable(&payload, &Array::of1(&buf));
-        }
+        let total = QUANTUM * 8; // target blocks (keep in sync with worklet)
+        render_and_post(&mut synth, &bus_for_cb, &port, total);
 
         let onmsg = Closure::wrap(Box::new(move |ev: web_sys::MessageEvent| {
-            while let Some(msg) = bus_for_cb.q.pop() {
-                match msg {
-                    Msg::NoteOn { note } => synth.note_on(note),
-                    Msg::NoteOff { note } => synth.note_off(note),
-                    Msg::SetMasterVolume(v) => synth.set_master_volume(v),
-                    Msg::SetAdsr { a, d, s, r } => synth.set_adsr(a, d, s, r),
-                    Msg::SetWaveform(wf) => synth.set_waveform(wf),
-                    Msg::SetFilter(ft) => synth.set_filter(ft),
-                }
-            }
-
             let data = ev.data();
             let need_val = Reflect::get(&data, &JsValue::from_str("need")).ok();
             let need_frames = need_val.and_then(|v| v.as_f64()).unwrap_or(QUANTUM as f64) as usize;
             let total = need_frames.div_ceil(QUANTUM) * QUANTUM;
-            let mut mono = vec![0.0f32; total];
-            let mut idx = 0;
-            while idx < total {
-                let end = (idx + QUANTUM).min(total);
-                render_block(&mut synth, &bus_for_cb, &mut mono[idx..end]);
-                idx = end;
-            }
-            let arr = Float32Array::new_with_length(total as u32);
-            arr.copy_from(&mono);
-            let buf: ArrayBuffer = arr.buffer();
-            let payload = Object::new();
-            let _ = Reflect::set(&payload, &JsValue::from_str("mono"), &arr);
-            let _ = port_for_cb.post_message_with_transferable(&payload, &Array::of1(&buf));
+            render_and_post(&mut synth, &bus_for_cb, &port_for_cb, total);
         }) as Box<dyn FnMut(_)>);
         port.set_onmessage(Some(onmsg.as_ref().unchecked_ref()));
         onmsg.forget();
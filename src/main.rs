@@ -6,7 +6,7 @@ use cpal::{
 
 use eframe::{NativeOptions, egui};
 use kbd_synth_min::{
-    audio::core::render_block,
+    audio::core::render_interleaved,
     gui::EguiUi,
     synth::{FilterType, SharedBus, Synth, Waveform},
 };
@@ -81,22 +81,19 @@ where
 {
     let mut synth = Synth::new(params.sample_rate, params.waveform, params.filter);
     let bus = params.bus;
+    bus.set_sample_rate(params.sample_rate);
     let channels = params.channels;
     let mut scratch: Vec<f32> = Vec::new();
 
     params.device.build_output_stream(
         params.config,
         move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
-            let nframes = data.len() / channels;
-            if scratch.len() != nframes {
-                scratch.resize(nframes, 0.0);
+            if scratch.len() != data.len() {
+                scratch.resize(data.len(), 0.0);
             }
-            render_block(&mut synth, &bus, &mut scratch);
-            for (i, frame) in data.chunks_mut(channels).enumerate() {
-                let s: T = cpal::Sample::from_sample(scratch[i]);
-                for ch in frame {
-                    *ch = s;
-                }
+            render_interleaved(&mut synth, &bus, &mut scratch, channels);
+            for (out, &s) in data.iter_mut().zip(scratch.iter()) {
+                *out = cpal::Sample::from_sample(s);
             }
         },
         params.err_fn,
@@ -0,0 +1,105 @@
+use std::f32::consts::{PI, TAU};
+
+pub const MAX_HARMONICS: usize = 64;
+const TABLE_SIZE: usize = 2048;
+
+/// 倍音ごとの振幅と位相（位相は 0.0〜1.0 で1周期）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spectrum {
+    pub amps: [f32; MAX_HARMONICS],
+    pub phases: [f32; MAX_HARMONICS],
+}
+
+impl Default for Spectrum {
+    fn default() -> Self {
+        let mut amps = [0.0; MAX_HARMONICS];
+        amps[0] = 1.0;
+        Self {
+            amps,
+            phases: [0.0; MAX_HARMONICS],
+        }
+    }
+}
+
+impl Spectrum {
+    /// 1/k で減衰する全倍音（ノコギリ波近似）
+    pub fn saw() -> Self {
+        let mut s = Self::default();
+        for (i, a) in s.amps.iter_mut().enumerate() {
+            *a = 1.0 / (i + 1) as f32;
+        }
+        s
+    }
+
+    /// 1/k で減衰する奇数倍音（矩形波近似）
+    pub fn square() -> Self {
+        let mut s = Self::default();
+        for (i, a) in s.amps.iter_mut().enumerate() {
+            *a = if i % 2 == 0 {
+                1.0 / (i + 1) as f32
+            } else {
+                0.0
+            };
+        }
+        s
+    }
+
+    /// 倍音数ごとのテーブルを作る（tables[k - 1] は 1〜k 倍音を含む）
+    /// 全テーブル共通のゲインでピークを 1.0 に揃える
+    pub fn to_wavetable(&self) -> Wavetable {
+        let mut acc = vec![0.0f32; TABLE_SIZE];
+        let mut tables = Vec::with_capacity(MAX_HARMONICS);
+        let mut peak = 0.0f32;
+        for (i, (a, p)) in self.amps.iter().zip(self.phases.iter()).enumerate() {
+            if *a != 0.0 {
+                let k = (i + 1) as f32;
+                for (n, y) in acc.iter_mut().enumerate() {
+                    let phase = TAU * n as f32 / TABLE_SIZE as f32;
+                    *y += a * (k * phase + p * TAU).sin();
+                }
+            }
+            peak = acc.iter().fold(peak, |m, y| m.max(y.abs()));
+            tables.push(acc.clone().into_boxed_slice());
+        }
+        if peak > 0.0 {
+            for t in tables.iter_mut() {
+                t.iter_mut().for_each(|y| *y /= peak);
+            }
+        }
+        Wavetable { tables }
+    }
+}
+
+/// 位相増分（ラジアン/サンプル）に対してナイキスト未満に収まる倍音数
+#[inline]
+fn audible_harmonics(phase_inc: f32) -> usize {
+    if phase_inc <= 0.0 {
+        return MAX_HARMONICS;
+    }
+    ((PI / phase_inc).ceil() as usize)
+        .saturating_sub(1)
+        .clamp(1, MAX_HARMONICS)
+}
+
+/// `Spectrum` から生成した帯域制限済みウェーブテーブル
+#[derive(Debug)]
+pub struct Wavetable {
+    tables: Vec<Box<[f32]>>,
+}
+
+impl Wavetable {
+    /// ノートの高さに応じてナイキスト以上の倍音を含まないテーブルを選んで読む
+    pub fn sample(&self, phase: f32, phase_inc: f32) -> f32 {
+        let Some(table) = self
+            .tables
+            .get(audible_harmonics(phase_inc).min(self.tables.len()).max(1) - 1)
+        else {
+            return 0.0;
+        };
+        let pos = phase / TAU * TABLE_SIZE as f32;
+        let i0 = (pos as usize) % TABLE_SIZE;
+        let i1 = (i0 + 1) % TABLE_SIZE;
+        let frac = pos - pos.floor();
+        table[i0] + (table[i1] - table[i0]) * frac
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub enum EnvState {
     #[default]
     Idle,
@@ -150,6 +150,16 @@ impl Adsr {
         self.level
     }
 
+    #[inline]
+    pub fn state(&self) -> EnvState {
+        self.state
+    }
+
+    #[inline]
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
     #[inline]
     pub fn is_active(&self) -> bool {
         self.level > 0.0
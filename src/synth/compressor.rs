@@ -0,0 +1,116 @@
+use crate::synth::fx::db_to_gain;
+
+const MIN_DB: f32 = -120.0;
+
+/// マスターのコンプレッサー設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompressorParams {
+    pub enabled: bool,
+    pub threshold_db: f32,
+    pub ratio: f32,      // 1.0 で圧縮しない
+    pub knee_db: f32,    // ソフトニーの幅（0 でハードニー）
+    pub attack_ms: f32,  // ゲインを下げるまでの時間
+    pub release_ms: f32, // ゲインが戻るまでの時間
+    pub makeup_db: f32,
+}
+
+impl Default for CompressorParams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -18.0,
+            ratio: 4.0,
+            knee_db: 6.0,
+            attack_ms: 10.0,
+            release_ms: 150.0,
+            makeup_db: 0.0,
+        }
+    }
+}
+
+impl CompressorParams {
+    /// 入力レベル [dB] に対するゲインの変化量 [dB]（0 以下）
+    #[inline]
+    pub fn gain_db(&self, level_db: f32) -> f32 {
+        let over = level_db - self.threshold_db;
+        let slope = 1.0 / self.ratio.max(1.0) - 1.0;
+        let knee = self.knee_db.max(0.0);
+        if 2.0 * over <= -knee {
+            0.0
+        } else if 2.0 * over < knee {
+            // ニーの中は 2 次曲線でつなぐ
+            let x = over + 0.5 * knee;
+            slope * x * x / (2.0 * knee)
+        } else {
+            slope * over
+        }
+    }
+}
+
+/// 左右共通のゲインで圧縮するフィードフォワード型コンプレッサー
+///
+/// ピークレベルから静特性で求めた減衰量 [dB] を、
+/// 下げるときはアタック、戻すときはリリースの時定数で追従させる。
+#[derive(Debug, Clone)]
+pub struct Compressor {
+    params: CompressorParams,
+    attack: f32,
+    release: f32,
+    makeup: f32,
+    env_db: f32,   // 平滑化した減衰量 [dB]
+    min_gain: f32, // take_min_gain までの最小ゲイン（GUI 表示用、メイクアップは含まない）
+}
+
+impl Compressor {
+    pub fn new(sr: f32, params: CompressorParams) -> Self {
+        let mut c = Self {
+            params,
+            attack: 0.0,
+            release: 0.0,
+            makeup: 1.0,
+            env_db: 0.0,
+            min_gain: 1.0,
+        };
+        c.set_params(sr, params);
+        c
+    }
+
+    pub fn set_params(&mut self, sr: f32, params: CompressorParams) {
+        let coef = |ms: f32| (-1.0 / (ms.max(0.01) * 0.001 * sr)).exp();
+        self.attack = coef(params.attack_ms);
+        self.release = coef(params.release_ms);
+        self.makeup = db_to_gain(params.makeup_db);
+        if params.enabled != self.params.enabled {
+            self.env_db = 0.0;
+        }
+        self.params = params;
+    }
+
+    /// 前回呼んでからの最小ゲイン（1.0 なら圧縮していない）
+    pub fn take_min_gain(&mut self) -> f32 {
+        std::mem::replace(&mut self.min_gain, db_to_gain(self.env_db))
+    }
+
+    #[inline]
+    pub fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        if !self.params.enabled {
+            return frame;
+        }
+        let peak = frame[0].abs().max(frame[1].abs());
+        let level_db = if peak > 0.0 {
+            (20.0 * peak.log10()).max(MIN_DB)
+        } else {
+            MIN_DB
+        };
+        let target = self.params.gain_db(level_db);
+        let coef = if target < self.env_db {
+            self.attack
+        } else {
+            self.release
+        };
+        self.env_db = target + (self.env_db - target) * coef;
+        let gain = db_to_gain(self.env_db);
+        self.min_gain = self.min_gain.min(gain);
+        frame.map(|x| x * gain * self.makeup)
+    }
+}
@@ -1,103 +1,553 @@
+use std::{f32::consts::TAU, sync::Arc};
+
 use crate::synth::{
+    additive::Wavetable,
     adsr::Adsr,
-    filter::{Filter, FilterTrait, FilterType},
-    note::Note,
-    osc::{Osc, Waveform},
+    compressor::{Compressor, CompressorParams},
+    eq::{Eq, EqParams},
+    filter::{DualFilter, Filter, FilterRouting, FilterSlot, FilterType, VowelMod, comb_freq},
+    fx::{Effect, FxChain, FxType, MAX_FX},
+    granular::{GrainCloud, GrainParams},
+    instrument::Instrument,
+    limiter::{Limiter, LimiterParams},
+    note::{Note, hz_to_midi},
+    osc::{Osc, PhaseMode, SubOsc, Waveform},
+    pluck::{Pluck, PluckParams},
+    rng::Rng,
+    sample::{LoopMode, Sample, SampleParams, SamplePlayer},
+    smooth::Smoothed,
+    stereo::{SpreadMode, Stereo, apply_width, key_position, pan_gains},
+    telemetry::{Telemetry, VoiceInfo},
 };
 
-#[derive(Clone, Copy, Default)]
+/// ボイスの音源の種類
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum VoiceType {
+    #[default]
+    Osc,
+    Pluck(PluckParams),
+    Sample(SampleParams),
+    Instrument, // 読み込んだ Instrument のゾーンから選ぶ
+    Granular(GrainParams),
+}
+
+#[derive(Clone, Default)]
 struct Voice {
     on: bool,
     note: Note,
     phase: f32,
+    kind: VoiceType, // ノートオン時の音源の種類
     asdr: Adsr,
     osc: Osc,
-    filter: Option<Filter>,
+    sub: Osc,
+    pluck: Pluck,
+    player: SamplePlayer,
+    region: usize, // Instrument のとき再生中のゾーン
+    stale: bool,   // 差し替え前の楽器（fading_instrument）のゾーンを鳴らしている
+    gain: f32,     // Instrument のときのベロシティによる音量
+    grains: GrainCloud,
+    filters: DualFilter,
+    lfo_phase: f32, // 母音変調の LFO（0.0〜1.0）
+    pan_pos: f32,   // スプレッドによる位置（-1.0〜1.0）
+    pan: [f32; 2],  // 左右のゲイン
+}
+
+impl Voice {
+    fn new(sr: f32) -> Self {
+        Self {
+            pluck: Pluck::new(sr),
+            filters: DualFilter::with_lines(sr),
+            ..Default::default()
+        }
+    }
 }
 
-const MAX_VOICES: usize = 16;
+pub const MAX_VOICES: usize = 16;
+const CONTROL_BLOCK: u32 = 16; // フィルタ係数など重い平滑化はこのサンプル数ごとに更新する
+const DEFAULT_SMOOTHING: f32 = 0.02; // 秒
 
 pub struct Synth {
     sr: f32,
     voices: [Voice; MAX_VOICES],
-    master_volume: f32,
+    master_volume: Smoothed,
     attack: f32,
     decay: f32,
     sustain: f32,
     release: f32,
+    voice_type: VoiceType,
     waveform: Waveform,
-    filter_type: Option<FilterType>,
+    shape: Smoothed, // 矩形波のパルス幅 / 三角波のカーブ
+    wavetable: Option<Arc<Wavetable>>,
+    sample: Option<Arc<Sample>>,
+    instrument: Option<Arc<Instrument>>,
+    fading_instrument: Option<Arc<Instrument>>, // 差し替え前の楽器（リリース中のボイスが鳴り終わるまで保持）
+    filter_types: [Option<FilterType>; 2],      // 現在ボイスに反映している値
+    filter_targets: [Option<FilterType>; 2],
+    filter_from: [Option<FilterType>; 2], // 補間の始点
+    filter_ramp: [Smoothed; 2],           // 補間の進み具合（0.0〜1.0）
+    filter_routing: FilterRouting,
+    balance: Smoothed,
+    vowel_mod: VowelMod,
+    key_track: f32, // フィルタのキートラッキング量（1.0 でノートと同じだけ動く）
+    key_center: u8, // キートラッキングでカットオフがそのままになるノート
+    sub_osc: Option<SubOsc>,
+    sub_level: Smoothed,
+    phase_mode: PhaseMode,
+    rng: Rng,
+    frames: u64,       // 起動からの経過サンプル数（FreeRun の位相計算用）
+    smoothing: u32,    // パラメータ変更にかけるサンプル数
+    control_left: u32, // 次の制御レート更新までのサンプル数
+    pan: Smoothed,
+    spread: Smoothed,
+    spread_mode: SpreadMode,
+    width: Smoothed,
+    alternate: bool, // SpreadMode::Alternate で次のボイスを右に置くか
+    muted: bool,     // マスターが 0 で処理を止めている
+    fx: FxChain,
+    eq: Eq,
+    compressor: Compressor,
+    limiter: Limiter,
 }
 
 impl Synth {
     pub fn new(sr: f32, waveform: Waveform, filter_type: Option<FilterType>) -> Self {
         Self {
             sr,
-            voices: [Voice::default(); MAX_VOICES],
-            master_volume: 0.2,
+            voices: std::array::from_fn(|_| Voice::new(sr)),
+            master_volume: Smoothed::new(0.2),
             attack: 0.0,
             decay: 0.5,
             sustain: 1.0,
             release: 0.5,
+            voice_type: VoiceType::Osc,
             waveform,
-            filter_type,
+            shape: Smoothed::new(shape_of(waveform).unwrap_or(0.0)),
+            wavetable: None,
+            sample: None,
+            instrument: None,
+            fading_instrument: None,
+            filter_types: [filter_type, None],
+            filter_targets: [filter_type, None],
+            filter_from: [filter_type, None],
+            filter_ramp: [Smoothed::new(1.0); 2],
+            filter_routing: FilterRouting::Serial,
+            balance: Smoothed::new(0.5),
+            vowel_mod: VowelMod::default(),
+            key_track: 0.0,
+            key_center: 60,
+            sub_osc: None,
+            sub_level: Smoothed::new(0.0),
+            phase_mode: PhaseMode::Reset,
+            rng: Rng::default(),
+            frames: 0,
+            smoothing: (DEFAULT_SMOOTHING * sr) as u32,
+            control_left: 0,
+            pan: Smoothed::new(0.0),
+            spread: Smoothed::new(0.0),
+            spread_mode: SpreadMode::Key,
+            width: Smoothed::new(1.0),
+            alternate: false,
+            muted: false,
+            fx: FxChain::default(),
+            eq: Eq::new(sr, EqParams::default()),
+            compressor: Compressor::new(sr, CompressorParams::default()),
+            limiter: Limiter::new(sr, LimiterParams::default()),
+        }
+    }
+
+    /// PhaseMode に応じた開始位相（0.0〜1.0）
+    fn start_phase(&mut self, freq_hz: f32) -> f32 {
+        match self.phase_mode {
+            PhaseMode::Reset => 0.0,
+            PhaseMode::Random => self.rng.next_f32(),
+            PhaseMode::FreeRun => {
+                // 起動時から回り続けていた場合の位相を逆算する
+                (freq_hz as f64 * self.frames as f64 / self.sr as f64).fract() as f32
+            }
+        }
+    }
+
+    /// 新しいボイスのスプレッド上の位置
+    fn next_pan_pos(&mut self, freq_hz: f32) -> f32 {
+        match self.spread_mode {
+            SpreadMode::Key => key_position(hz_to_midi(freq_hz)),
+            SpreadMode::Alternate => {
+                self.alternate = !self.alternate;
+                if self.alternate { -1.0 } else { 1.0 }
+            }
         }
     }
 
-    pub fn note_on(&mut self, note: Note) {
+    fn voice_pan(&self, pan_pos: f32) -> [f32; 2] {
+        pan_gains(self.pan.value() + self.spread.value() * pan_pos)
+    }
+
+    pub fn note_on(&mut self, note: Note, velocity: u8) {
+        if self.voice_type == VoiceType::Instrument {
+            self.instrument_note_on(note, velocity);
+            return;
+        }
         let mut adsr = Adsr::new(self.attack, self.decay, self.sustain, self.release, self.sr);
         adsr.note_on();
         if let Some(v) = self.voices.iter_mut().find(|v| v.on && v.note == note) {
             v.asdr = adsr;
-            let _ = v.filter.as_mut().map(|f| f.reset());
+            v.filters.reset();
+            v.stale = false;
+            // 撥弦は弾き直し、サンプルは頭から再生し直す
+            v.kind = self.voice_type;
+            match self.voice_type {
+                VoiceType::Pluck(params) => {
+                    v.pluck.excite(note.into(), self.sr, params, &mut self.rng);
+                }
+                VoiceType::Sample(params) => {
+                    if let Some(smp) = self.sample.as_deref() {
+                        v.player = SamplePlayer::new(note.into(), self.sr, smp, &params);
+                    }
+                }
+                VoiceType::Granular(_) => v.grains = GrainCloud::new(note.into()),
+                VoiceType::Osc | VoiceType::Instrument => {}
+            }
             return;
         }
-        if let Some(voice) = self.voices.iter_mut().find(|v| !v.on) {
+        if let Some(idx) = self.voices.iter().position(|v| !v.on) {
+            let freq: f32 = note.into();
+            let phase = self.start_phase(freq);
+            let sub = self.sub_osc.unwrap_or_default();
+            let sub_phase = self.start_phase(sub.freq(freq));
+            let filter_types = self.tracked_filters(freq);
+            let pan_pos = self.next_pan_pos(freq);
+            let pan = self.voice_pan(pan_pos);
+            let voice = &mut self.voices[idx];
             voice.on = true;
             voice.note = note;
             voice.phase = 0.0;
+            voice.stale = false;
+            voice.kind = self.voice_type;
+            match self.voice_type {
+                VoiceType::Pluck(params) => {
+                    voice.pluck.excite(freq, self.sr, params, &mut self.rng);
+                }
+                VoiceType::Sample(params) => {
+                    voice.player = match self.sample.as_deref() {
+                        Some(smp) => SamplePlayer::new(freq, self.sr, smp, &params),
+                        None => SamplePlayer::default(),
+                    };
+                }
+                VoiceType::Granular(_) => voice.grains = GrainCloud::new(freq),
+                VoiceType::Osc | VoiceType::Instrument => {}
+            }
             voice.asdr = adsr;
-            voice.osc = Osc::new(note.into(), self.sr, self.waveform);
-            voice.filter = self
-                .filter_type
-                .map(|filter_type| Filter::new(filter_type, self.sr));
+            voice.osc = Osc::new(freq, self.sr, self.waveform).with_phase(phase);
+            voice.sub = Osc::new(sub.freq(freq), self.sr, sub.waveform()).with_phase(sub_phase);
+            voice.filters.set(filter_types, self.sr, freq);
+            voice.lfo_phase = 0.0;
+            voice.pan_pos = pan_pos;
+            voice.pan = pan;
         }
     }
 
+    /// キー・ベロシティ・ラウンドロビンに合うゾーンをすべて鳴らす（レイヤー）
+    fn instrument_note_on(&mut self, note: Note, velocity: u8) {
+        // next_pan_pos が &mut self を取るので Arc を複製して借用を切る（self が保持しているので解放は起きない）
+        let (Some(inst), Some(key)) = (self.instrument.clone(), note.midi()) else {
+            return;
+        };
+        // 同じノートが鳴っていればリリースし、新しいボイスで鳴らし直す
+        for v in self.voices.iter_mut().filter(|v| v.on && v.note == note) {
+            v.asdr.note_off();
+        }
+
+        let freq: f32 = note.into();
+        let vel = velocity.clamp(1, 127);
+        let gain = (vel as f32 / 127.0).powi(2);
+        let filter_types = self.tracked_filters(freq);
+        for (idx, region) in inst.regions.iter().enumerate() {
+            if !region.matches(key, vel) || !region.in_turn(inst.round_robin(region)) {
+                continue;
+            }
+            let Some(vidx) = self.voices.iter().position(|v| !v.on) else {
+                break;
+            };
+            let pan_pos = self.next_pan_pos(freq);
+            let pan = self.voice_pan(pan_pos);
+            let voice = &mut self.voices[vidx];
+            let [a, d, s, r] =
+                region
+                    .ampeg
+                    .unwrap_or([self.attack, self.decay, self.sustain, self.release]);
+            let mut adsr = Adsr::new(a, d, s, r, self.sr);
+            adsr.note_on();
+            let smp = &inst.samples[region.sample];
+            voice.on = true;
+            voice.note = note;
+            voice.kind = VoiceType::Instrument;
+            voice.region = idx;
+            voice.stale = false;
+            voice.gain = gain;
+            voice.asdr = adsr;
+            voice.player = SamplePlayer::new(freq, self.sr, smp, &region.params);
+            voice.sub = Osc::new(
+                self.sub_osc.unwrap_or_default().freq(freq),
+                self.sr,
+                self.sub_osc.unwrap_or_default().waveform(),
+            );
+            voice.filters.set(filter_types, self.sr, freq);
+            voice.lfo_phase = 0.0;
+            voice.pan_pos = pan_pos;
+            voice.pan = pan;
+        }
+        inst.advance_round_robin(key, vel);
+    }
+
     pub fn note_off(&mut self, note: Note) {
         for v in self.voices.iter_mut() {
             if v.on && v.note == note {
+                // ワンショットはノートオフを無視して最後まで鳴らす
+                let mode = match v.kind {
+                    VoiceType::Sample(params) => Some(params.mode),
+                    VoiceType::Instrument => match v.stale {
+                        true => self.fading_instrument.as_deref(),
+                        false => self.instrument.as_deref(),
+                    }
+                    .and_then(|inst| inst.regions.get(v.region))
+                    .map(|r| r.params.mode),
+                    _ => None,
+                };
+                if mode == Some(LoopMode::OneShot) {
+                    continue;
+                }
+                v.player.release();
                 v.asdr.note_off();
             }
         }
     }
 
-    pub fn next_sample(&mut self) -> f32 {
-        if self.master_volume == 0.0 {
-            return 0.0;
+    /// 1フレーム分のステレオ出力 [L, R]
+    pub fn next_frame(&mut self) -> [f32; 2] {
+        self.frames = self.frames.wrapping_add(1);
+        if self.control_left == 0 {
+            self.update_control();
+            self.control_left = CONTROL_BLOCK;
+        }
+        self.control_left -= 1;
+        let master = self.master_volume.next();
+        let sub_level = self.sub_level.next();
+        let width = self.width.next();
+        if master == 0.0 && !self.master_volume.is_ramping() {
+            // FX も止まるので、残響が戻したときに鳴り出さないよう消しておく
+            if !self.muted {
+                self.fx.reset();
+                self.muted = true;
+            }
+            return self.limiter.process([0.0; 2]);
         }
-        let mut sample = 0.0;
+        self.muted = false;
+        let mut frame = [0.0; 2];
         for voice in self.voices.iter_mut() {
             if voice.on {
+                let mut side = 0.0; // フィルタを通さない左右差（グラニュラーのみ）
                 let env = voice.asdr.next_sample();
                 if env <= 0.0 {
                     voice.on = false;
                     continue;
                 }
-                let osc_sample = voice.osc.next_sample();
-                let osc_sample = voice
-                    .filter
-                    .as_mut()
-                    .map(|f| f.process(osc_sample))
-                    .unwrap_or(osc_sample);
-                sample += osc_sample * env;
+                let osc_sample = match (voice.kind, self.waveform, self.wavetable.as_deref()) {
+                    (VoiceType::Pluck(_), _, _) => voice.pluck.next_sample(),
+                    (VoiceType::Sample(params), _, _) => match self.sample.as_deref() {
+                        Some(smp) => voice.player.next_sample(smp, &params),
+                        None => 0.0,
+                    },
+                    (VoiceType::Granular(params), _, _) => match self.sample.as_deref() {
+                        Some(smp) => {
+                            // フィルタはモノラルなので中央成分だけを通す
+                            let (l, r) =
+                                voice
+                                    .grains
+                                    .next_frame(smp, &params, self.sr, &mut self.rng);
+                            side = 0.5 * (l - r);
+                            0.5 * (l + r)
+                        }
+                        None => 0.0,
+                    },
+                    (VoiceType::Instrument, _, _) => match voice.stale {
+                        true => self.fading_instrument.as_deref(),
+                        false => self.instrument.as_deref(),
+                    }
+                    .and_then(|inst| {
+                        let region = inst.regions.get(voice.region)?;
+                        let smp = &inst.samples[region.sample];
+                        Some(voice.player.next_sample(smp, &region.params) * voice.gain)
+                    })
+                    .unwrap_or(0.0),
+                    (VoiceType::Osc, Waveform::Additive, Some(table)) => {
+                        voice.osc.next_sample_table(table)
+                    }
+                    (VoiceType::Osc, _, _) => voice.osc.next_sample(),
+                };
+                // サンプルを最後まで再生したらボイスを解放する
+                if voice.player.is_done()
+                    && matches!(voice.kind, VoiceType::Sample(_) | VoiceType::Instrument)
+                {
+                    voice.on = false;
+                }
+                let sub_sample = match self.sub_osc {
+                    Some(_) => voice.sub.next_sample() * sub_level,
+                    None => 0.0,
+                };
+                if self.vowel_mod.is_active() {
+                    let m = self.vowel_mod;
+                    let lfo = (voice.lfo_phase * TAU).sin();
+                    voice.lfo_phase = (voice.lfo_phase + m.lfo_rate / self.sr).fract();
+                    voice
+                        .filters
+                        .modulate_vowel(self.sr, lfo * m.lfo_depth + env * m.env_depth);
+                }
+                let out = voice
+                    .filters
+                    .process(self.filter_routing, osc_sample, sub_sample);
+                frame[0] += (out + side) * env * voice.pan[0];
+                frame[1] += (out - side) * env * voice.pan[1];
+            }
+        }
+        let frame = self.fx.process(frame);
+        let frame = apply_width(frame, width).map(|x| x * master);
+        let frame = self.compressor.process(self.eq.process(frame));
+        self.limiter.process(frame)
+    }
+
+    /// 制御レートで平滑化するパラメータを進める
+    fn update_control(&mut self) {
+        for idx in 0..2 {
+            if !self.filter_ramp[idx].is_ramping() {
+                continue;
             }
+            let t = self.filter_ramp[idx].advance(CONTROL_BLOCK);
+            let target = self.filter_targets[idx];
+            let ft = match (self.filter_from[idx], target) {
+                (Some(from), Some(to)) => from.approach(to, t).or(target),
+                _ => target,
+            };
+            self.apply_filter(idx, ft);
+        }
+        if self.balance.is_ramping() {
+            let b = self.balance.advance(CONTROL_BLOCK);
+            self.apply_balance(b);
+        }
+        self.eq.update(CONTROL_BLOCK);
+        if self.pan.is_ramping() || self.spread.is_ramping() {
+            let pan = self.pan.advance(CONTROL_BLOCK);
+            let spread = self.spread.advance(CONTROL_BLOCK);
+            self.apply_pan(pan, spread);
+        }
+        if self.shape.is_ramping() {
+            let v = self.shape.advance(CONTROL_BLOCK);
+            self.apply_shape(v);
+        }
+    }
+
+    fn apply_balance(&mut self, b: f32) {
+        self.filter_routing = self.filter_routing.with_balance(b);
+    }
+
+    fn apply_pan(&mut self, pan: f32, spread: f32) {
+        for voice in self.voices.iter_mut() {
+            voice.pan = pan_gains(pan + spread * voice.pan_pos);
         }
-        sample * self.master_volume
+    }
+
+    fn apply_shape(&mut self, v: f32) {
+        self.waveform = with_shape(self.waveform, v);
+        for voice in self.voices.iter_mut() {
+            voice.osc.set_waveform(self.waveform);
+        }
+    }
+
+    /// パラメータ変更にかける時間（秒）
+    pub fn set_smoothing(&mut self, sec: f32) {
+        self.smoothing = (sec.max(0.0) * self.sr) as u32;
+    }
+
+    pub fn set_stereo(&mut self, stereo: Stereo) {
+        self.pan
+            .set_target(stereo.pan.clamp(-1.0, 1.0), self.smoothing);
+        self.spread
+            .set_target(stereo.spread.clamp(0.0, 1.0), self.smoothing);
+        self.width
+            .set_target(stereo.width.clamp(0.0, 2.0), self.smoothing);
+        self.spread_mode = stereo.mode;
+        // 平滑化しないときは update_control を待たずに反映する
+        if !self.pan.is_ramping() && !self.spread.is_ramping() {
+            self.apply_pan(self.pan.value(), self.spread.value());
+        }
+    }
+
+    /// エフェクトを差し替え、古いもの（差し替えられなければ渡したもの）を返す
+    pub fn load_fx(&mut self, slot: usize, effect: Option<Box<Effect>>) -> Option<Box<Effect>> {
+        self.fx.load(slot, effect)
+    }
+
+    pub fn set_fx(&mut self, slot: usize, ft: FxType) {
+        self.fx.set_params(slot, ft);
+    }
+
+    pub fn set_fx_mix(&mut self, slot: usize, mix: f32) {
+        self.fx.set_mix(slot, mix, self.smoothing);
+    }
+
+    pub fn set_fx_bypass(&mut self, slot: usize, bypass: bool) {
+        self.fx.set_bypass(slot, bypass, self.smoothing);
+    }
+
+    pub fn set_tempo(&mut self, bpm: f32) {
+        self.fx.set_tempo(bpm);
+    }
+
+    pub fn set_fx_order(&mut self, order: [u8; MAX_FX]) {
+        self.fx.set_order(order, self.smoothing);
+    }
+
+    pub fn set_eq(&mut self, params: EqParams) {
+        self.eq.set_params(params, self.smoothing);
+    }
+
+    pub fn set_compressor(&mut self, params: CompressorParams) {
+        self.compressor.set_params(self.sr, params);
+    }
+
+    /// 前回呼んでからのコンプレッサーの最小ゲイン
+    pub fn take_compressor_gain(&mut self) -> f32 {
+        self.compressor.take_min_gain()
+    }
+
+    pub fn set_limiter(&mut self, params: LimiterParams) {
+        self.limiter.set_params(self.sr, params);
+    }
+
+    /// ボイスの状態を t に書き込む
+    pub fn voice_telemetry(&self, t: &mut Telemetry) {
+        t.active_voices = 0;
+        for (info, v) in t.voices.iter_mut().zip(self.voices.iter()) {
+            *info = if v.on {
+                t.active_voices += 1;
+                VoiceInfo {
+                    note: v.note,
+                    state: v.asdr.state(),
+                    level: v.asdr.level(),
+                }
+            } else {
+                VoiceInfo::default()
+            };
+        }
+    }
+
+    /// 前回呼んでからのリミッターの最小ゲイン
+    pub fn take_limiter_gain(&mut self) -> f32 {
+        self.limiter.take_min_gain()
     }
 
     pub fn set_master_volume(&mut self, vol: f32) {
-        self.master_volume = vol.clamp(0.0, 1.0);
+        self.master_volume
+            .set_target(vol.clamp(0.0, 1.0), self.smoothing);
     }
 
     pub fn set_adsr(&mut self, a: f32, d: f32, s: f32, r: f32) {
@@ -116,34 +566,235 @@ impl Synth {
         }
     }
 
+    pub fn set_voice_type(&mut self, new: VoiceType) {
+        self.voice_type = new;
+        // 発音中の同種のボイスにはパラメータだけ反映（次のノートから種類が切り替わる）
+        for v in self.voices.iter_mut().filter(|v| v.on) {
+            match (new, v.kind) {
+                (VoiceType::Pluck(params), VoiceType::Pluck(_)) => {
+                    v.pluck.set_params(self.sr, params);
+                    v.kind = new;
+                }
+                (VoiceType::Sample(_), VoiceType::Sample(_))
+                | (VoiceType::Granular(_), VoiceType::Granular(_)) => v.kind = new,
+                _ => {}
+            }
+        }
+    }
+
+    /// サンプルを差し替え、古いサンプルを返す（解放は呼び出し側で）
+    /// 再生中のボイスは新しいサンプルの同じ位置から読み続ける
+    pub fn set_sample(&mut self, sample: Arc<Sample>) -> Option<Arc<Sample>> {
+        self.sample.replace(sample)
+    }
+
     pub fn set_waveform(&mut self, new: Waveform) {
+        // 同じ波形でパルス幅・カーブだけが変わったなら滑らかに動かす
+        match (shape_of(self.waveform), shape_of(new)) {
+            (Some(_), Some(v)) if same_kind(self.waveform, new) => {
+                self.shape.set_target(v, self.smoothing);
+                if !self.shape.is_ramping() {
+                    self.apply_shape(v);
+                }
+                return;
+            }
+            (_, v) => self.shape = Smoothed::new(v.unwrap_or(0.0)),
+        }
         self.waveform = new;
         for v in self.voices.iter_mut() {
             v.osc.set_waveform(new);
         }
     }
 
-    pub fn set_filter(&mut self, new: Option<FilterType>) {
-        self.filter_type = new;
+    /// 新しいテーブルに差し替え、古いテーブルを返す（解放は呼び出し側で）
+    pub fn set_wavetable(&mut self, table: Arc<Wavetable>) -> Option<Arc<Wavetable>> {
+        self.wavetable.replace(table)
+    }
+
+    /// 楽器を差し替え、手放した楽器を返す（解放は呼び出し側で）
+    /// 古い楽器で鳴っているボイスはリリースさせ、鳴り終わるまで古い楽器を保持する
+    pub fn set_instrument(&mut self, inst: Arc<Instrument>) -> Option<Arc<Instrument>> {
+        // 保持できるのは1つ前の楽器までなので、さらに前の楽器のボイスはここで止める
+        let older = self.fading_instrument.take();
+        if older.is_some() {
+            for v in self.voices.iter_mut().filter(|v| v.stale) {
+                v.on = false;
+            }
+        }
         for v in self.voices.iter_mut() {
-            match (new, v.filter.as_mut()) {
+            if v.on && v.kind == VoiceType::Instrument {
+                v.stale = true;
+                v.player.release();
+                v.asdr.note_off();
+            }
+        }
+        self.fading_instrument = self.instrument.replace(inst);
+        older
+    }
+
+    /// 差し替え前の楽器のボイスがすべて鳴り終わったら、その楽器を返す（解放は呼び出し側で）
+    pub fn take_faded_instrument(&mut self) -> Option<Arc<Instrument>> {
+        if self.voices.iter().any(|v| v.on && v.stale) {
+            return None;
+        }
+        self.fading_instrument.take()
+    }
+
+    pub fn set_sub_osc(&mut self, new: Option<SubOsc>) {
+        let was_on = self.sub_osc.is_some();
+        self.sub_osc = new;
+        let Some(sub) = new else {
+            return;
+        };
+        if was_on {
+            self.sub_level.set_target(sub.level, self.smoothing);
+        } else {
+            self.sub_level = Smoothed::new(sub.level);
+        }
+        // 発音中のボイスは位相を保ったまま周波数と波形だけ差し替える
+        for v in self.voices.iter_mut() {
+            v.sub.set_freq(sub.freq(v.note.into()), self.sr);
+            v.sub.set_waveform(sub.waveform());
+        }
+    }
+
+    pub fn set_phase_mode(&mut self, mode: PhaseMode) {
+        self.phase_mode = mode;
+    }
+
+    /// ノートの周波数に合わせてキートラッキングしたフィルタ設定
+    fn tracked_filters(&self, note_hz: f32) -> [Option<FilterType>; 2] {
+        let (amount, center) = (self.key_track, self.key_center);
+        self.filter_types
+            .map(|ft| ft.map(|ft| ft.key_tracked(note_hz, amount, center)))
+    }
+
+    pub fn set_filter_key_track(&mut self, amount: f32, center: u8) {
+        self.key_track = amount;
+        self.key_center = center.min(127);
+        self.apply_filter(0, self.filter_types[0]);
+        self.apply_filter(1, self.filter_types[1]);
+    }
+
+    pub fn set_filter_routing(&mut self, routing: FilterRouting) {
+        match (self.filter_routing.balance(), routing.balance()) {
+            // 接続が同じ種類ならバランスだけ滑らかに動かす
+            (Some(_), Some(b))
+                if std::mem::discriminant(&self.filter_routing)
+                    == std::mem::discriminant(&routing) =>
+            {
+                self.filter_routing = routing.with_balance(self.balance.value());
+                self.balance.set_target(b, self.smoothing);
+                if !self.balance.is_ramping() {
+                    self.apply_balance(b);
+                }
+            }
+            (_, b) => {
+                self.filter_routing = routing;
+                self.balance = Smoothed::new(b.unwrap_or(0.5));
+            }
+        }
+    }
+
+    pub fn set_vowel_mod(&mut self, m: VowelMod) {
+        self.vowel_mod = m;
+        if !m.is_active() {
+            // 変調を切ったら元の母音に戻す
+            for v in self.voices.iter_mut() {
+                v.filters.modulate_vowel(self.sr, 0.0);
+            }
+        }
+    }
+
+    /// 同じ種類のフィルタのパラメータ変更は smoothing の時間をかけて補間する
+    pub fn set_filter(&mut self, slot: FilterSlot, new: Option<FilterType>) {
+        let idx = slot as usize;
+        let current = self.filter_types[idx];
+        self.filter_targets[idx] = new;
+        match (current, new) {
+            (Some(from), Some(to)) if from.approach(to, 0.0).is_some() => {
+                self.filter_from[idx] = current;
+                self.filter_ramp[idx] = Smoothed::new(0.0);
+                self.filter_ramp[idx].set_target(1.0, self.smoothing);
+                if !self.filter_ramp[idx].is_ramping() {
+                    self.apply_filter(idx, new);
+                }
+            }
+            _ => {
+                self.filter_ramp[idx] = Smoothed::new(1.0);
+                self.apply_filter(idx, new);
+            }
+        }
+    }
+
+    /// 全ボイスのフィルタに反映する（同じ種類なら状態を保ったまま係数だけ更新）
+    fn apply_filter(&mut self, idx: usize, new: Option<FilterType>) {
+        self.filter_types[idx] = new;
+        let (amount, center) = (self.key_track, self.key_center);
+        for v in self.voices.iter_mut() {
+            let note_hz: f32 = v.note.into();
+            let new = new.map(|ft| ft.key_tracked(note_hz, amount, center));
+            let filter = &mut v.filters.slots[idx];
+            let mut fresh = None; // 作り直すフィルタ（コムなら遅延線も消す）
+            match (new, filter.as_mut()) {
                 (None, _) => {
-                    v.filter = None;
+                    *filter = None;
                 }
                 (Some(ft), None) => {
-                    v.filter = Some(Filter::new(ft, self.sr));
+                    fresh = Some(Filter::new(ft, self.sr, note_hz));
                 }
                 (Some(FilterType::OnePoleLpf(c)), Some(Filter::OnePoleLpf(f))) => {
                     f.set_cutoff(self.sr, c); // 型は同じ → 係数更新だけ
                 }
-                (Some(FilterType::TwoPoleLpf(c, q)), Some(Filter::TwoPoleLpf(f))) => {
-                    f.set_params(self.sr, c, q);
+                (Some(FilterType::Svf(c, q, m)), Some(Filter::Svf(f))) => {
+                    f.set_params(self.sr, c, q, m);
+                }
+                (Some(FilterType::Ladder(c, r, d)), Some(Filter::Ladder(f))) => {
+                    f.set_params(self.sr, c, r, d);
+                }
+                (Some(FilterType::Formant(vowel, set)), Some(Filter::Formant(f))) => {
+                    f.set_params(self.sr, vowel, set);
+                }
+                (Some(FilterType::Comb(tune, g, mode)), Some(Filter::Comb(f))) => {
+                    f.set_params(self.sr, comb_freq(note_hz, tune), g, mode);
+                }
+                (Some(FilterType::Phaser(c, fb, n)), Some(Filter::Phaser(f))) => {
+                    f.set_params(self.sr, c, fb, n);
                 }
+                (Some(ft), Some(Filter::Biquad(f))) => match ft.biquad() {
+                    // 双2次同士ならモードが変わっても状態を保ったまま係数だけ更新
+                    Some((mode, c, q, g)) => f.set_params(self.sr, mode, c, q, g),
+                    None => fresh = Some(Filter::new(ft, self.sr, note_hz)),
+                },
                 (Some(ft), Some(_old_other_type)) => {
                     // 型が変わる → 作り直す（必要なら新規に reset 済み）
-                    v.filter = Some(Filter::new(ft, self.sr));
+                    fresh = Some(Filter::new(ft, self.sr, note_hz));
                 }
             }
+            if fresh.is_some() {
+                v.filters.set_slot(idx, fresh);
+            }
         }
     }
 }
+
+/// パルス幅・カーブを持つ波形ならその値
+fn shape_of(w: Waveform) -> Option<f32> {
+    match w {
+        Waveform::Square { pulse_width } => Some(pulse_width),
+        Waveform::Triangle { curve } => Some(curve),
+        _ => None,
+    }
+}
+
+fn with_shape(w: Waveform, v: f32) -> Waveform {
+    match w {
+        Waveform::Square { .. } => Waveform::Square { pulse_width: v },
+        Waveform::Triangle { .. } => Waveform::Triangle { curve: v },
+        other => other,
+    }
+}
+
+fn same_kind(a: Waveform, b: Waveform) -> bool {
+    std::mem::discriminant(&a) == std::mem::discriminant(&b)
+}
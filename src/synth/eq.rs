@@ -0,0 +1,158 @@
+use crate::synth::{
+    filter::{Biquad, BiquadMode, FilterTrait},
+    smooth::Smoothed,
+};
+
+pub const EQ_BANDS: usize = 4;
+
+/// 各バンドのフィルタの形（低域シェルフ / ピーク 2 つ / 高域シェルフ）
+const BAND_MODES: [BiquadMode; EQ_BANDS] = [
+    BiquadMode::LowShelf,
+    BiquadMode::Peaking,
+    BiquadMode::Peaking,
+    BiquadMode::HighShelf,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqBand {
+    pub freq: f32,    // [Hz]
+    pub gain_db: f32, // [dB]
+    pub q: f32,
+}
+
+/// マスターの 4 バンド・パラメトリック EQ の設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqParams {
+    pub enabled: bool,
+    pub bands: [EqBand; EQ_BANDS],
+}
+
+impl Default for EqParams {
+    fn default() -> Self {
+        let band = |freq, q| EqBand {
+            freq,
+            gain_db: 0.0,
+            q,
+        };
+        Self {
+            enabled: false,
+            bands: [
+                band(100.0, 0.7),
+                band(500.0, 1.0),
+                band(2500.0, 1.0),
+                band(8000.0, 0.7),
+            ],
+        }
+    }
+}
+
+/// 周波数と Q は対数で、ゲインは dB で平滑化する
+#[derive(Debug, Clone, Copy, Default)]
+struct BandRamp {
+    freq: Smoothed, // log2(Hz)
+    gain: Smoothed, // dB
+    q: Smoothed,    // log2(Q)
+}
+
+impl BandRamp {
+    fn new(band: EqBand) -> Self {
+        Self {
+            freq: Smoothed::new(band.freq.max(1.0).log2()),
+            gain: Smoothed::new(band.gain_db),
+            q: Smoothed::new(band.q.max(0.1).log2()),
+        }
+    }
+
+    fn set_target(&mut self, band: EqBand, samples: u32) {
+        self.freq.set_target(band.freq.max(1.0).log2(), samples);
+        self.gain.set_target(band.gain_db, samples);
+        self.q.set_target(band.q.max(0.1).log2(), samples);
+    }
+
+    fn is_ramping(&self) -> bool {
+        self.freq.is_ramping() || self.gain.is_ramping() || self.q.is_ramping()
+    }
+
+    /// n サンプル進めた (周波数, ゲイン, Q)
+    fn advance(&mut self, n: u32) -> (f32, f32, f32) {
+        (
+            self.freq.advance(n).exp2(),
+            self.gain.advance(n),
+            self.q.advance(n).exp2(),
+        )
+    }
+}
+
+/// Biquad を直列につないだステレオのパラメトリック EQ
+#[derive(Debug, Clone)]
+pub struct Eq {
+    sr: f32,
+    enabled: bool,
+    ramps: [BandRamp; EQ_BANDS],
+    filters: [[Biquad; 2]; EQ_BANDS],
+}
+
+impl Eq {
+    pub fn new(sr: f32, params: EqParams) -> Self {
+        let mut eq = Self {
+            sr,
+            enabled: params.enabled,
+            ramps: params.bands.map(BandRamp::new),
+            filters: Default::default(),
+        };
+        for i in 0..EQ_BANDS {
+            eq.apply_band(i, 0);
+        }
+        eq
+    }
+
+    /// samples サンプルかけて新しい設定へ移る
+    pub fn set_params(&mut self, params: EqParams, samples: u32) {
+        // 止まっていた間の古い状態を鳴らさないよう空にしてから有効にする
+        if params.enabled && !self.enabled {
+            self.reset();
+        }
+        self.enabled = params.enabled;
+        for (ramp, band) in self.ramps.iter_mut().zip(params.bands) {
+            ramp.set_target(band, samples);
+        }
+        if samples == 0 {
+            for i in 0..EQ_BANDS {
+                self.apply_band(i, 0);
+            }
+        }
+    }
+
+    /// 平滑化中のバンドの係数を n サンプル分進めて計算し直す（制御レートで呼ぶ）
+    pub fn update(&mut self, n: u32) {
+        for i in 0..EQ_BANDS {
+            if self.ramps[i].is_ramping() {
+                self.apply_band(i, n);
+            }
+        }
+    }
+
+    fn apply_band(&mut self, i: usize, n: u32) {
+        let (freq, gain, q) = self.ramps[i].advance(n);
+        for f in self.filters[i].iter_mut() {
+            f.set_params(self.sr, BAND_MODES[i], freq, q, gain);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for f in self.filters.iter_mut().flatten() {
+            f.reset();
+        }
+    }
+
+    #[inline]
+    pub fn process(&mut self, mut frame: [f32; 2]) -> [f32; 2] {
+        if !self.enabled {
+            return frame;
+        }
+        for band in self.filters.iter_mut() {
+            frame = [band[0].process(frame[0]), band[1].process(frame[1])];
+        }
+        frame
+    }
+}
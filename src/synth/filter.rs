@@ -1,45 +1,419 @@
+mod comb;
+mod formant;
+mod ladder;
+mod phaser;
+mod response;
+mod svf;
+
+pub use comb::{Comb, CombLine, CombMode};
+pub use formant::{Formant, VowelMod, VowelSet};
+pub use ladder::Ladder;
+pub use phaser::{MAX_STAGES, Phaser};
+pub use response::Complex;
+use response::z_inv;
+pub use svf::{Svf, SvfOutputs};
+
+use crate::synth::note::midi_to_hz;
+
+pub const MIN_CUTOFF: f32 = 20.0; // カットオフの可聴範囲（上限はフィルタ側でもナイキストに丸める）
+pub const MAX_CUTOFF: f32 = 20000.0;
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FilterType {
-    OnePoleLpf(f32),      // カットオフ周波数
-    TwoPoleLpf(f32, f32), // カットオフ周波数とレゾナンス
+    OnePoleLpf(f32),          // カットオフ周波数
+    TwoPoleLpf(f32, f32),     // カットオフ周波数とレゾナンス
+    TwoPoleHpf(f32, f32),     // カットオフ周波数とレゾナンス
+    BandPass(f32, f32),       // 中心周波数とQ（スカート一定: ピークゲイン = Q）
+    BandPassPeak(f32, f32),   // 中心周波数とQ（ピークゲイン 0dB）
+    Notch(f32, f32),          // 中心周波数とQ
+    AllPass(f32, f32),        // 中心周波数とQ
+    Peaking(f32, f32, f32),   // 中心周波数とQとゲイン[dB]
+    LowShelf(f32, f32, f32),  // 肩の周波数とQとゲイン[dB]
+    HighShelf(f32, f32, f32), // 肩の周波数とQとゲイン[dB]
+    Svf(f32, f32, f32),       // カットオフ周波数とQとモーフ（0 = LP, 0.5 = BP, 1 = HP）
+    Ladder(f32, f32, f32),    // カットオフ周波数とレゾナンス（0〜1）とドライブ（0〜1）
+    Formant(f32, VowelSet),   // 母音（0 = A, 1 = E, 2 = I, 3 = O, 4 = U。間は補間）
+    Comb(f32, f32, CombMode), // ノートからの音程[半音]とゲイン（-1〜1）
+    Phaser(f32, f32, u8),     // 中心周波数とフィードバック（-1〜1）と段数
+}
+
+impl FilterType {
+    /// 双2次フィルタで実現する型なら (モード, 周波数, Q, ゲイン[dB])
+    pub fn biquad(&self) -> Option<(BiquadMode, f32, f32, f32)> {
+        match *self {
+            FilterType::OnePoleLpf(_)
+            | FilterType::Svf(..)
+            | FilterType::Ladder(..)
+            | FilterType::Formant(..)
+            | FilterType::Comb(..)
+            | FilterType::Phaser(..) => None,
+            FilterType::TwoPoleLpf(c, q) => Some((BiquadMode::Lowpass, c, q, 0.0)),
+            FilterType::TwoPoleHpf(c, q) => Some((BiquadMode::Highpass, c, q, 0.0)),
+            FilterType::BandPass(c, q) => Some((BiquadMode::BandPass, c, q, 0.0)),
+            FilterType::BandPassPeak(c, q) => Some((BiquadMode::BandPassPeak, c, q, 0.0)),
+            FilterType::Notch(c, q) => Some((BiquadMode::Notch, c, q, 0.0)),
+            FilterType::AllPass(c, q) => Some((BiquadMode::AllPass, c, q, 0.0)),
+            FilterType::Peaking(c, q, g) => Some((BiquadMode::Peaking, c, q, g)),
+            FilterType::LowShelf(c, q, g) => Some((BiquadMode::LowShelf, c, q, g)),
+            FilterType::HighShelf(c, q, g) => Some((BiquadMode::HighShelf, c, q, g)),
+        }
+    }
+
+    /// カットオフ（中心・肩）周波数。キートラッキングの対象外なら None
+    pub fn cutoff(&self) -> Option<f32> {
+        let c = match *self {
+            FilterType::OnePoleLpf(c)
+            | FilterType::TwoPoleLpf(c, _)
+            | FilterType::TwoPoleHpf(c, _)
+            | FilterType::BandPass(c, _)
+            | FilterType::BandPassPeak(c, _)
+            | FilterType::Notch(c, _)
+            | FilterType::AllPass(c, _)
+            | FilterType::Peaking(c, _, _)
+            | FilterType::LowShelf(c, _, _)
+            | FilterType::HighShelf(c, _, _)
+            | FilterType::Svf(c, _, _)
+            | FilterType::Ladder(c, _, _)
+            | FilterType::Phaser(c, _, _) => c,
+            // フォルマントは母音で、コムはノートの高さで決まる
+            FilterType::Formant(..) | FilterType::Comb(..) => return None,
+        };
+        Some(c)
+    }
+
+    /// カットオフ周波数だけを差し替える
+    pub fn with_cutoff(mut self, hz: f32) -> Self {
+        match &mut self {
+            FilterType::OnePoleLpf(c)
+            | FilterType::TwoPoleLpf(c, _)
+            | FilterType::TwoPoleHpf(c, _)
+            | FilterType::BandPass(c, _)
+            | FilterType::BandPassPeak(c, _)
+            | FilterType::Notch(c, _)
+            | FilterType::AllPass(c, _)
+            | FilterType::Peaking(c, _, _)
+            | FilterType::LowShelf(c, _, _)
+            | FilterType::HighShelf(c, _, _)
+            | FilterType::Svf(c, _, _)
+            | FilterType::Ladder(c, _, _)
+            | FilterType::Phaser(c, _, _) => *c = hz,
+            FilterType::Formant(..) | FilterType::Comb(..) => {}
+        }
+        self
+    }
+
+    /// 同じ種類のフィルタなら、連続なパラメータを target へ t（0〜1）だけ近づけた値
+    /// 周波数と Q は対数で補間する。種類が違えば None
+    pub fn approach(self, target: Self, t: f32) -> Option<Self> {
+        use FilterType as F;
+        let lin = |a: f32, b: f32| a + (b - a) * t;
+        let log = |a: f32, b: f32| {
+            if a > 0.0 && b > 0.0 {
+                a * (b / a).powf(t)
+            } else {
+                lin(a, b)
+            }
+        };
+        Some(match (self, target) {
+            (F::OnePoleLpf(c0), F::OnePoleLpf(c)) => F::OnePoleLpf(log(c0, c)),
+            (F::TwoPoleLpf(c0, q0), F::TwoPoleLpf(c, q)) => F::TwoPoleLpf(log(c0, c), log(q0, q)),
+            (F::TwoPoleHpf(c0, q0), F::TwoPoleHpf(c, q)) => F::TwoPoleHpf(log(c0, c), log(q0, q)),
+            (F::BandPass(c0, q0), F::BandPass(c, q)) => F::BandPass(log(c0, c), log(q0, q)),
+            (F::BandPassPeak(c0, q0), F::BandPassPeak(c, q)) => {
+                F::BandPassPeak(log(c0, c), log(q0, q))
+            }
+            (F::Notch(c0, q0), F::Notch(c, q)) => F::Notch(log(c0, c), log(q0, q)),
+            (F::AllPass(c0, q0), F::AllPass(c, q)) => F::AllPass(log(c0, c), log(q0, q)),
+            (F::Peaking(c0, q0, g0), F::Peaking(c, q, g)) => {
+                F::Peaking(log(c0, c), log(q0, q), lin(g0, g))
+            }
+            (F::LowShelf(c0, q0, g0), F::LowShelf(c, q, g)) => {
+                F::LowShelf(log(c0, c), log(q0, q), lin(g0, g))
+            }
+            (F::HighShelf(c0, q0, g0), F::HighShelf(c, q, g)) => {
+                F::HighShelf(log(c0, c), log(q0, q), lin(g0, g))
+            }
+            (F::Svf(c0, q0, m0), F::Svf(c, q, m)) => F::Svf(log(c0, c), log(q0, q), lin(m0, m)),
+            (F::Ladder(c0, r0, d0), F::Ladder(c, r, d)) => {
+                F::Ladder(log(c0, c), lin(r0, r), lin(d0, d))
+            }
+            (F::Formant(v0, _), F::Formant(v, set)) => F::Formant(lin(v0, v), set),
+            (F::Comb(t0, g0, _), F::Comb(tune, g, mode)) => {
+                F::Comb(lin(t0, tune), lin(g0, g), mode)
+            }
+            (F::Phaser(c0, f0, _), F::Phaser(c, fb, n)) => F::Phaser(log(c0, c), lin(f0, fb), n),
+            _ => return None,
+        })
+    }
+
+    /// キートラッキング: center のノートを基準に、ノートの高さに合わせてカットオフを動かす
+    /// amount = 1.0 でカットオフがノートと同じだけ（1オクターブで2倍）動く
+    pub fn key_tracked(self, note_hz: f32, amount: f32, center: u8) -> Self {
+        let Some(cutoff) = self.cutoff().filter(|_| amount != 0.0 && note_hz > 0.0) else {
+            return self;
+        };
+        let ratio = (note_hz / midi_to_hz(center as f32)).powf(amount);
+        self.with_cutoff((cutoff * ratio).clamp(MIN_CUTOFF, MAX_CUTOFF))
+    }
 }
 
 #[derive(Clone, Copy)]
 pub enum Filter {
     OnePoleLpf(OnePoleLpf),
-    TwoPoleLpf(TwoPoleLpf),
+    Biquad(Biquad),
+    Svf(Svf),
+    Ladder(Ladder),
+    Formant(Formant),
+    Comb(Comb),
+    Phaser(Phaser),
 }
 
 impl Filter {
-    pub fn new(filter_type: FilterType, sr: f32) -> Self {
-        match filter_type {
-            FilterType::OnePoleLpf(cutoff) => Filter::OnePoleLpf(OnePoleLpf::new(sr, cutoff)),
-            FilterType::TwoPoleLpf(cutoff, q) => Filter::TwoPoleLpf(TwoPoleLpf::new(sr, cutoff, q)),
+    /// note_hz はコムのようにノートの高さに合わせるフィルタで使う
+    pub fn new(filter_type: FilterType, sr: f32, note_hz: f32) -> Self {
+        match (filter_type, filter_type.biquad()) {
+            (_, Some((mode, cutoff, q, gain_db))) => {
+                Filter::Biquad(Biquad::new(sr, mode, cutoff, q, gain_db))
+            }
+            (FilterType::OnePoleLpf(cutoff), _) => Filter::OnePoleLpf(OnePoleLpf::new(sr, cutoff)),
+            (FilterType::Svf(cutoff, q, morph), _) => Filter::Svf(Svf::new(sr, cutoff, q, morph)),
+            (FilterType::Ladder(cutoff, res, drive), _) => {
+                Filter::Ladder(Ladder::new(sr, cutoff, res, drive))
+            }
+            (FilterType::Formant(vowel, set), _) => Filter::Formant(Formant::new(sr, vowel, set)),
+            (FilterType::Comb(tune, gain, mode), _) => {
+                Filter::Comb(Comb::new(sr, comb_freq(note_hz, tune), gain, mode))
+            }
+            (FilterType::Phaser(cutoff, fb, stages), _) => {
+                Filter::Phaser(Phaser::new(sr, cutoff, fb, stages))
+            }
+            (ft, None) => unreachable!("{ft:?} has no filter implementation"),
+        }
+    }
+}
+
+/// ボイスごとの2つのフィルタの接続方法
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FilterRouting {
+    #[default]
+    Serial, // 1 → 2
+    Parallel {
+        balance: f32, // 0.0 = フィルタ1のみ, 0.5 = 両方, 1.0 = フィルタ2のみ
+    },
+    Split {
+        main: FilterSlot, // メインのオシレータ（サンプル等を含む）を通すフィルタ
+        sub: FilterSlot,  // サブオシレータを通すフィルタ
+        balance: f32,
+    },
+}
+
+impl FilterRouting {
+    pub fn balance(&self) -> Option<f32> {
+        match *self {
+            FilterRouting::Serial => None,
+            FilterRouting::Parallel { balance } | FilterRouting::Split { balance, .. } => {
+                Some(balance)
+            }
+        }
+    }
+
+    pub fn with_balance(mut self, b: f32) -> Self {
+        match &mut self {
+            FilterRouting::Serial => {}
+            FilterRouting::Parallel { balance } | FilterRouting::Split { balance, .. } => {
+                *balance = b
+            }
+        }
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FilterSlot {
+    #[default]
+    One,
+    Two,
+}
+
+/// balance から (フィルタ1, フィルタ2) のゲインを求める（中央で両方とも 1.0）
+#[inline]
+fn balance_gains(balance: f32) -> (f32, f32) {
+    let b = balance.clamp(0.0, 1.0);
+    ((2.0 * (1.0 - b)).min(1.0), (2.0 * b).min(1.0))
+}
+
+/// ボイスごとの2つのフィルタスロット（空のスロットは素通し）
+#[derive(Clone, Default)]
+pub struct DualFilter {
+    pub slots: [Option<Filter>; 2],
+    lines: [CombLine; 2], // スロットごとのコムの遅延線
+}
+
+impl DualFilter {
+    /// 遅延線を持たない（コムは素通しになる）ので、周波数応答を見る用
+    pub fn new(filter_types: [Option<FilterType>; 2], sr: f32, note_hz: f32) -> Self {
+        Self {
+            slots: filter_types.map(|ft| ft.map(|ft| Filter::new(ft, sr, note_hz))),
+            lines: Default::default(),
+        }
+    }
+
+    /// ボイス用（コムの遅延線をここで確保する）
+    pub fn with_lines(sr: f32) -> Self {
+        Self {
+            slots: [None; 2],
+            lines: std::array::from_fn(|_| CombLine::new(sr)),
+        }
+    }
+
+    /// 遅延線を作り直さずにフィルタを入れ替える
+    pub fn set(&mut self, filter_types: [Option<FilterType>; 2], sr: f32, note_hz: f32) {
+        for (idx, ft) in filter_types.into_iter().enumerate() {
+            self.set_slot(idx, ft.map(|ft| Filter::new(ft, sr, note_hz)));
         }
     }
+
+    pub fn set_slot(&mut self, idx: usize, filter: Option<Filter>) {
+        if let Some(Filter::Comb(_)) = filter {
+            self.lines[idx].clear();
+        }
+        self.slots[idx] = filter;
+    }
+
+    /// main はメインの音源、sub はサブオシレータの出力
+    pub fn process(&mut self, routing: FilterRouting, main: f32, sub: f32) -> f32 {
+        let [one, two] = &mut self.slots;
+        let [l1, l2] = &mut self.lines;
+        let mut one = |x| run(one, l1, x);
+        let mut two = |x| run(two, l2, x);
+        match routing {
+            FilterRouting::Serial => two(one(main + sub)),
+            FilterRouting::Parallel { balance } => {
+                let (g1, g2) = balance_gains(balance);
+                one(main + sub) * g1 + two(main + sub) * g2
+            }
+            FilterRouting::Split {
+                main: m,
+                sub: s,
+                balance,
+            } => {
+                let route = |slot| {
+                    (if m == slot { main } else { 0.0 }) + (if s == slot { sub } else { 0.0 })
+                };
+                let (g1, g2) = balance_gains(balance);
+                one(route(FilterSlot::One)) * g1 + two(route(FilterSlot::Two)) * g2
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for (f, line) in self.slots.iter_mut().zip(&mut self.lines) {
+            if let Some(f) = f {
+                f.reset(line);
+            }
+        }
+    }
+
+    /// メインのオシレータから見た2つのフィルタ全体の周波数応答
+    pub fn response(&self, routing: FilterRouting, sr: f32, hz: f32) -> Complex {
+        let h = |f: &Option<Filter>| f.as_ref().map_or(Complex::ONE, |f| f.response(sr, hz));
+        let [one, two] = &self.slots;
+        match routing {
+            FilterRouting::Serial => h(one) * h(two),
+            FilterRouting::Parallel { balance } => {
+                let (g1, g2) = balance_gains(balance);
+                h(one) * g1 + h(two) * g2
+            }
+            FilterRouting::Split { main, balance, .. } => {
+                let (g1, g2) = balance_gains(balance);
+                match main {
+                    FilterSlot::One => h(one) * g1,
+                    FilterSlot::Two => h(two) * g2,
+                }
+            }
+        }
+    }
+
+    /// フォルマントのスロットの母音をずらす
+    #[inline]
+    pub fn modulate_vowel(&mut self, sr: f32, offset: f32) {
+        for f in self.slots.iter_mut().flatten() {
+            if let Filter::Formant(f) = f {
+                f.modulate(sr, offset);
+            }
+        }
+    }
+}
+
+#[inline]
+fn run(filter: &mut Option<Filter>, line: &mut CombLine, x: f32) -> f32 {
+    filter.as_mut().map_or(x, |f| f.process(line, x))
+}
+
+/// コムの遅延をノートの周期（から tune 半音ずらした周期）にする
+#[inline]
+pub fn comb_freq(note_hz: f32, tune: f32) -> f32 {
+    note_hz * 2.0f32.powf(tune / 12.0)
 }
 
 pub trait FilterTrait {
     fn process(&mut self, input: f32) -> f32;
     fn reset(&mut self);
+
+    /// 現在の係数での周波数応答 H(e^{jω})（非線形なフィルタは小信号での近似）
+    fn response(&self, sr: f32, hz: f32) -> Complex;
+
+    fn magnitude_db(&self, sr: f32, hz: f32) -> f32 {
+        20.0 * self.response(sr, hz).abs().max(1e-10).log10()
+    }
+
+    /// 位相 [rad]（-π〜π）
+    fn phase(&self, sr: f32, hz: f32) -> f32 {
+        self.response(sr, hz).arg()
+    }
 }
 
-impl FilterTrait for Filter {
-    fn process(&mut self, x: f32) -> f32 {
+// コムは遅延線を外から受け取るので FilterTrait は実装しない
+impl Filter {
+    pub fn process(&mut self, line: &mut CombLine, x: f32) -> f32 {
         match self {
             Filter::OnePoleLpf(f) => f.process(x),
-            Filter::TwoPoleLpf(f) => f.process(x),
+            Filter::Biquad(f) => f.process(x),
+            Filter::Svf(f) => f.process(x),
+            Filter::Ladder(f) => f.process(x),
+            Filter::Formant(f) => f.process(x),
+            Filter::Comb(f) => f.process(line, x),
+            Filter::Phaser(f) => f.process(x),
         }
     }
-    fn reset(&mut self) {
+    pub fn reset(&mut self, line: &mut CombLine) {
         match self {
             Filter::OnePoleLpf(f) => f.reset(),
-            Filter::TwoPoleLpf(f) => f.reset(),
+            Filter::Biquad(f) => f.reset(),
+            Filter::Svf(f) => f.reset(),
+            Filter::Ladder(f) => f.reset(),
+            Filter::Formant(f) => f.reset(),
+            Filter::Comb(f) => {
+                f.reset();
+                line.clear();
+            }
+            Filter::Phaser(f) => f.reset(),
+        }
+    }
+    pub fn response(&self, sr: f32, hz: f32) -> Complex {
+        match self {
+            Filter::OnePoleLpf(f) => f.response(sr, hz),
+            Filter::Biquad(f) => f.response(sr, hz),
+            Filter::Svf(f) => f.response(sr, hz),
+            Filter::Ladder(f) => f.response(sr, hz),
+            Filter::Formant(f) => f.response(sr, hz),
+            Filter::Comb(f) => f.response(sr, hz),
+            Filter::Phaser(f) => f.response(sr, hz),
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy)]
 pub struct OnePoleLpf {
     cutoff: f32, // カットオフ周波数 [0 ~ sr/2]
     a: f32,      // フィルタ係数
@@ -76,12 +450,34 @@ impl FilterTrait for OnePoleLpf {
     fn reset(&mut self) {
         self.y1 = 0.0;
     }
+
+    fn response(&self, sr: f32, hz: f32) -> Complex {
+        // H = a / (1 - (1 - a) z^-1)
+        Complex::new(self.a, 0.0) / (Complex::ONE - z_inv(sr, hz) * (1.0 - self.a))
+    }
 }
 
-#[derive(Clone, Copy, Default)]
-pub struct TwoPoleLpf {
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum BiquadMode {
+    #[default]
+    Lowpass,
+    Highpass,
+    BandPass,     // スカート一定
+    BandPassPeak, // ピーク一定
+    Notch,
+    AllPass,
+    Peaking,
+    LowShelf,
+    HighShelf,
+}
+
+/// RBJ Audio EQ Cookbook の双2次フィルタ（転置直接形II）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Biquad {
+    mode: BiquadMode,
     cutoff: f32,
-    q: f32, // レゾナンス
+    q: f32,       // レゾナンス
+    gain_db: f32, // Peaking / Shelf のゲイン
     b0: f32,
     b1: f32,
     b2: f32,
@@ -91,11 +487,13 @@ pub struct TwoPoleLpf {
     y2: f32, // 前回の出力
 }
 
-impl TwoPoleLpf {
-    pub fn new(sr: f32, cutoff: f32, q: f32) -> Self {
+impl Biquad {
+    pub fn new(sr: f32, mode: BiquadMode, cutoff: f32, q: f32, gain_db: f32) -> Self {
         let mut f = Self {
+            mode,
             cutoff,
             q,
+            gain_db,
             b0: 0.0,
             b1: 0.0,
             b2: 0.0,
@@ -108,9 +506,11 @@ impl TwoPoleLpf {
         f
     }
 
-    pub fn set_params(&mut self, sr: f32, cutoff: f32, q: f32) {
+    pub fn set_params(&mut self, sr: f32, mode: BiquadMode, cutoff: f32, q: f32, gain_db: f32) {
+        self.mode = mode;
         self.cutoff = cutoff;
         self.q = q;
+        self.gain_db = gain_db;
         self.update_coefficients(sr);
     }
 
@@ -119,13 +519,69 @@ impl TwoPoleLpf {
         let w0 = 2.0 * std::f32::consts::PI * f0 / sr;
         let (sw, cw0) = w0.sin_cos();
         let alpha = sw / (2.0 * self.q.max(1e-6));
+        let a = 10f32.powf(self.gain_db / 40.0); // Peaking / Shelf 用の振幅
+        let two_sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
 
-        let b0 = (1.0 - cw0) / 2.0;
-        let b1 = 1.0 - cw0;
-        let b2 = (1.0 - cw0) / 2.0;
-        let a0 = 1.0 + alpha;
-        let a1 = -2.0 * cw0;
-        let a2 = 1.0 - alpha;
+        let (b0, b1, b2, a0, a1, a2) = match self.mode {
+            BiquadMode::Lowpass => (
+                (1.0 - cw0) / 2.0,
+                1.0 - cw0,
+                (1.0 - cw0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cw0,
+                1.0 - alpha,
+            ),
+            BiquadMode::Highpass => (
+                (1.0 + cw0) / 2.0,
+                -(1.0 + cw0),
+                (1.0 + cw0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cw0,
+                1.0 - alpha,
+            ),
+            BiquadMode::BandPass => (
+                sw / 2.0,
+                0.0,
+                -sw / 2.0,
+                1.0 + alpha,
+                -2.0 * cw0,
+                1.0 - alpha,
+            ),
+            BiquadMode::BandPassPeak => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cw0, 1.0 - alpha),
+            BiquadMode::Notch => (1.0, -2.0 * cw0, 1.0, 1.0 + alpha, -2.0 * cw0, 1.0 - alpha),
+            BiquadMode::AllPass => (
+                1.0 - alpha,
+                -2.0 * cw0,
+                1.0 + alpha,
+                1.0 + alpha,
+                -2.0 * cw0,
+                1.0 - alpha,
+            ),
+            BiquadMode::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cw0,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cw0,
+                1.0 - alpha / a,
+            ),
+            BiquadMode::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cw0 + two_sqrt_a_alpha),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cw0),
+                a * ((a + 1.0) - (a - 1.0) * cw0 - two_sqrt_a_alpha),
+                (a + 1.0) + (a - 1.0) * cw0 + two_sqrt_a_alpha,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cw0),
+                (a + 1.0) + (a - 1.0) * cw0 - two_sqrt_a_alpha,
+            ),
+            BiquadMode::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cw0 + two_sqrt_a_alpha),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cw0),
+                a * ((a + 1.0) + (a - 1.0) * cw0 - two_sqrt_a_alpha),
+                (a + 1.0) - (a - 1.0) * cw0 + two_sqrt_a_alpha,
+                2.0 * ((a - 1.0) - (a + 1.0) * cw0),
+                (a + 1.0) - (a - 1.0) * cw0 - two_sqrt_a_alpha,
+            ),
+        };
 
         self.b0 = b0 / a0; // 正規化された係数
         self.b1 = b1 / a0;
@@ -135,7 +591,7 @@ impl TwoPoleLpf {
     }
 }
 
-impl FilterTrait for TwoPoleLpf {
+impl FilterTrait for Biquad {
     fn process(&mut self, input: f32) -> f32 {
         let y = self.b0 * input + self.y1;
         self.y1 = self.b1 * input - self.a1 * y + self.y2;
@@ -147,4 +603,12 @@ impl FilterTrait for TwoPoleLpf {
         self.y1 = 0.0;
         self.y2 = 0.0;
     }
+
+    fn response(&self, sr: f32, hz: f32) -> Complex {
+        let z1 = z_inv(sr, hz);
+        let z2 = z1 * z1;
+        let num = Complex::new(self.b0, 0.0) + z1 * self.b1 + z2 * self.b2;
+        let den = Complex::ONE + z1 * self.a1 + z2 * self.a2;
+        num / den
+    }
 }
@@ -0,0 +1,114 @@
+use crate::synth::filter::response::Complex;
+
+/// 遅延線の長さを決める最低周波数（MIDI ノート 0 ≒ 8.18Hz まで音程どおりに鳴らせる）
+/// これより低い周波数（tune で下げた場合など）はこの値に丸める
+const MIN_FREQ: f32 = 8.0;
+const MAX_GAIN: f32 = 0.99;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CombMode {
+    #[default]
+    FeedForward, // y = x + g x[n - D]
+    Feedback, // y = x + g y[n - D]
+}
+
+/// コムフィルタの遅延線（ボイスごとに生成時に1度だけ確保する）
+#[derive(Debug, Clone, Default)]
+pub struct CombLine {
+    buf: Box<[f32]>, // 空なら素通し（周波数応答を見るだけの DualFilter など）
+}
+
+impl CombLine {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            buf: vec![0.0; (sr / MIN_FREQ).ceil() as usize + 2].into_boxed_slice(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.buf.fill(0.0);
+    }
+}
+
+/// ノートの高さに合わせたコムフィルタ（係数と書き込み位置だけを持ち、遅延線は CombLine）
+/// gain が負なら奇数倍音側にピークが立つ（中空な音）
+#[derive(Clone, Copy)]
+pub struct Comb {
+    pos: usize,
+    delay: f32, // サンプル数（小数）
+    gain: f32,  // -0.99〜0.99
+    norm: f32,  // 白色雑音に対するパワーを 1 に揃える係数
+    mode: CombMode,
+}
+
+impl Comb {
+    pub fn new(sr: f32, freq: f32, gain: f32, mode: CombMode) -> Self {
+        let mut f = Self {
+            pos: 0,
+            delay: 1.0,
+            gain: 0.0,
+            norm: 1.0,
+            mode,
+        };
+        f.set_params(sr, freq, gain, mode);
+        f
+    }
+
+    pub fn set_params(&mut self, sr: f32, freq: f32, gain: f32, mode: CombMode) {
+        self.mode = mode;
+        self.gain = gain.clamp(-MAX_GAIN, MAX_GAIN);
+        let g2 = self.gain * self.gain;
+        self.norm = match mode {
+            CombMode::FeedForward => (1.0 + g2).sqrt().recip(),
+            CombMode::Feedback => (1.0 - g2).sqrt(),
+        };
+        // CombLine::new(sr) の長さに収まる
+        self.delay = (sr / freq.max(MIN_FREQ)).max(1.0);
+    }
+
+    /// delay サンプル前の値（線形補間）
+    #[inline]
+    fn tap(&self, buf: &[f32]) -> f32 {
+        let len = buf.len();
+        let delay = self.delay.min((len - 2) as f32);
+        let d = delay.floor() as usize;
+        let t = delay - d as f32;
+        let i0 = (self.pos + len - d) % len;
+        let i1 = (i0 + len - 1) % len;
+        buf[i0] + (buf[i1] - buf[i0]) * t
+    }
+
+    pub fn process(&mut self, line: &mut CombLine, input: f32) -> f32 {
+        let buf = &mut line.buf[..];
+        if buf.len() < 3 {
+            return input;
+        }
+        let delayed = self.tap(buf);
+        let y = input + self.gain * delayed;
+        let mut w = match self.mode {
+            CombMode::FeedForward => input,
+            CombMode::Feedback => y,
+        };
+        // デノーマル対策
+        if w.abs() < 1.0e-20 {
+            w = 0.0;
+        }
+        buf[self.pos] = w;
+        self.pos = (self.pos + 1) % buf.len();
+        y * self.norm
+    }
+
+    /// 遅延線の中身は CombLine::clear で消す
+    pub fn reset(&mut self) {
+        self.pos = 0;
+    }
+
+    pub fn response(&self, sr: f32, hz: f32) -> Complex {
+        // 小数遅延は理想的な e^{-jωD} とみなす（線形補間による高域の減衰は含まない）
+        let zd = Complex::from_polar(self.gain, -std::f32::consts::TAU * hz / sr * self.delay);
+        match self.mode {
+            CombMode::FeedForward => (Complex::ONE + zd) * self.norm,
+            CombMode::Feedback => Complex::new(self.norm, 0.0) / (Complex::ONE - zd),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::synth::filter::{FilterTrait, Svf, response::Complex};
+
+const BANDS: usize = 5;
+const VOWELS: usize = 5; // A, E, I, O, U
+const MAKEUP: f32 = 3.0; // 帯域が狭く音量が下がる分（ノコギリ波で約 -14dB）を補う
+
+/// フォルマントのデータセット
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum VowelSet {
+    #[default]
+    Male, // テノール
+    Female, // ソプラノ
+}
+
+/// 母音ごとのフォルマント（周波数 [Hz], 振幅 [dB], 帯域幅 [Hz]）
+/// Csound マニュアルのフォルマント表より
+struct Formants {
+    freq: [f32; BANDS],
+    db: [f32; BANDS],
+    bw: [f32; BANDS],
+}
+
+#[rustfmt::skip]
+const MALE: [Formants; VOWELS] = [
+    Formants { freq: [650.0, 1080.0, 2650.0, 2900.0, 3250.0], db: [0.0, -6.0, -7.0, -8.0, -22.0], bw: [80.0, 90.0, 120.0, 130.0, 140.0] },
+    Formants { freq: [400.0, 1700.0, 2600.0, 3200.0, 3580.0], db: [0.0, -14.0, -12.0, -14.0, -20.0], bw: [70.0, 80.0, 100.0, 120.0, 120.0] },
+    Formants { freq: [290.0, 1870.0, 2800.0, 3250.0, 3540.0], db: [0.0, -15.0, -18.0, -20.0, -30.0], bw: [40.0, 90.0, 100.0, 120.0, 120.0] },
+    Formants { freq: [400.0, 800.0, 2600.0, 2800.0, 3000.0], db: [0.0, -10.0, -12.0, -12.0, -26.0], bw: [40.0, 80.0, 100.0, 120.0, 120.0] },
+    Formants { freq: [350.0, 600.0, 2700.0, 2900.0, 3300.0], db: [0.0, -20.0, -17.0, -14.0, -26.0], bw: [40.0, 60.0, 100.0, 120.0, 120.0] },
+];
+
+#[rustfmt::skip]
+const FEMALE: [Formants; VOWELS] = [
+    Formants { freq: [800.0, 1150.0, 2900.0, 3900.0, 4950.0], db: [0.0, -6.0, -32.0, -20.0, -50.0], bw: [80.0, 90.0, 120.0, 130.0, 140.0] },
+    Formants { freq: [350.0, 2000.0, 2800.0, 3600.0, 4950.0], db: [0.0, -20.0, -15.0, -40.0, -56.0], bw: [60.0, 100.0, 120.0, 150.0, 200.0] },
+    Formants { freq: [270.0, 2140.0, 2950.0, 3900.0, 4950.0], db: [0.0, -12.0, -26.0, -26.0, -44.0], bw: [60.0, 90.0, 100.0, 120.0, 120.0] },
+    Formants { freq: [450.0, 800.0, 2830.0, 3800.0, 4950.0], db: [0.0, -11.0, -22.0, -22.0, -50.0], bw: [40.0, 80.0, 100.0, 120.0, 120.0] },
+    Formants { freq: [325.0, 700.0, 2700.0, 3800.0, 4950.0], db: [0.0, -16.0, -35.0, -40.0, -60.0], bw: [50.0, 60.0, 170.0, 180.0, 200.0] },
+];
+
+/// 母音の変調（LFO とアンプエンベロープで vowel を動かす）
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct VowelMod {
+    pub lfo_rate: f32,  // Hz
+    pub lfo_depth: f32, // 母音の数（1.0 で隣の母音まで）
+    pub env_depth: f32, // エンベロープ最大時のずれ（母音の数）
+}
+
+impl VowelMod {
+    pub fn is_active(&self) -> bool {
+        self.lfo_depth != 0.0 || self.env_depth != 0.0
+    }
+}
+
+/// 母音のフォルマントに合わせた並列バンドパス
+#[derive(Clone, Copy, Default)]
+pub struct Formant {
+    bands: [Svf; BANDS],
+    gains: [f32; BANDS],
+    set: VowelSet,
+    vowel: f32,   // 0.0 = A, 1.0 = E, 2.0 = I, 3.0 = O, 4.0 = U（間は補間）
+    current: f32, // 変調後の実際の vowel
+}
+
+impl Formant {
+    pub fn new(sr: f32, vowel: f32, set: VowelSet) -> Self {
+        let mut f = Self::default();
+        f.set_params(sr, vowel, set);
+        f
+    }
+
+    pub fn set_params(&mut self, sr: f32, vowel: f32, set: VowelSet) {
+        self.set = set;
+        self.vowel = vowel.clamp(0.0, (VOWELS - 1) as f32);
+        self.update(sr, self.vowel);
+    }
+
+    /// vowel を offset だけずらす（毎サンプル呼んでよい）
+    #[inline]
+    pub fn modulate(&mut self, sr: f32, offset: f32) {
+        let v = (self.vowel + offset).clamp(0.0, (VOWELS - 1) as f32);
+        if (v - self.current).abs() > 1e-4 {
+            self.update(sr, v);
+        }
+    }
+
+    fn update(&mut self, sr: f32, vowel: f32) {
+        self.current = vowel;
+        let table = match self.set {
+            VowelSet::Male => &MALE,
+            VowelSet::Female => &FEMALE,
+        };
+        let i = (vowel as usize).min(VOWELS - 2);
+        let t = vowel - i as f32;
+        let (a, b) = (&table[i], &table[i + 1]);
+        for (k, band) in self.bands.iter_mut().enumerate() {
+            // 周波数は対数で、振幅と帯域幅は線形で補間する
+            let freq = a.freq[k] * (b.freq[k] / a.freq[k]).powf(t);
+            let bw = a.bw[k] + (b.bw[k] - a.bw[k]) * t;
+            let db = a.db[k] + (b.db[k] - a.db[k]) * t;
+            band.set_params(sr, freq, freq / bw, 0.5);
+            self.gains[k] = MAKEUP * 10.0f32.powf(db / 20.0);
+        }
+    }
+}
+
+impl FilterTrait for Formant {
+    fn process(&mut self, input: f32) -> f32 {
+        self.bands
+            .iter_mut()
+            .zip(self.gains.iter())
+            .map(|(band, g)| band.process_multi(input).bp * g)
+            .sum()
+    }
+
+    fn reset(&mut self) {
+        self.bands.iter_mut().for_each(|b| b.reset());
+    }
+
+    fn response(&self, sr: f32, hz: f32) -> Complex {
+        self.bands
+            .iter()
+            .zip(self.gains.iter())
+            .fold(Complex::default(), |acc, (band, g)| {
+                acc + band.response_multi(sr, hz)[1] * *g
+            })
+    }
+}
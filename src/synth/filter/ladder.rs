@@ -0,0 +1,90 @@
+use crate::synth::filter::{
+    FilterTrait,
+    response::{Complex, blt_s},
+};
+
+const MAX_K: f32 = 4.2; // resonance = 1.0 で確実に自己発振させる
+
+/// Moog 型 4 ポール（24dB/oct）ラダーフィルタ
+/// 各段は TPT の 1 ポールで、段の入力に tanh の飽和を入れる。
+/// フィードバックは線形近似で解いてから非線形段を通す（ゼロディレイ）。
+#[derive(Clone, Copy, Default)]
+pub struct Ladder {
+    cutoff: f32,
+    resonance: f32, // 0.0〜1.0
+    drive: f32,     // 0.0〜1.0
+    g: f32,         // 1 ポールのゲイン G = g / (1 + g)
+    k: f32,         // フィードバック量
+    s: [f32; 4],    // 各段の状態
+}
+
+impl Ladder {
+    pub fn new(sr: f32, cutoff: f32, resonance: f32, drive: f32) -> Self {
+        let mut f = Self::default();
+        f.set_params(sr, cutoff, resonance, drive);
+        f
+    }
+
+    pub fn set_params(&mut self, sr: f32, cutoff: f32, resonance: f32, drive: f32) {
+        self.resonance = resonance.clamp(0.0, 1.0);
+        self.drive = drive.clamp(0.0, 1.0);
+        self.k = self.resonance * MAX_K;
+        self.set_cutoff(sr, cutoff);
+    }
+
+    #[inline]
+    pub fn set_cutoff(&mut self, sr: f32, cutoff: f32) {
+        self.cutoff = cutoff.clamp(1.0, 0.45 * sr);
+        let g = (std::f32::consts::PI * self.cutoff / sr).tan();
+        self.g = g / (1.0 + g);
+    }
+}
+
+impl FilterTrait for Ladder {
+    fn process(&mut self, input: f32) -> f32 {
+        let g = self.g;
+        let b = 1.0 - g;
+        let gain = 1.0 + 9.0 * self.drive;
+
+        // 線形なら y4 = G^4 u + sigma となる項
+        let sigma =
+            g * g * g * b * self.s[0] + g * g * b * self.s[1] + g * b * self.s[2] + b * self.s[3];
+        // レゾナンスで痩せる低域を入力側で補償する
+        // DC ゲイン 1/(1+k) を全部戻すと tanh の手前で最大 +14dB 余計に歪むので半分に留める
+        let x = input * gain * comp(self.k);
+        let u = (x - self.k * sigma) / (1.0 + self.k * g * g * g * g);
+
+        let mut stage_in = u.tanh();
+        let mut y = 0.0;
+        for s in self.s.iter_mut() {
+            let v = (stage_in - *s) * g;
+            y = v + *s;
+            *s = y + v;
+            // デノーマル対策
+            if s.abs() < 1.0e-20 {
+                *s = 0.0;
+            }
+            stage_in = y.tanh();
+        }
+        // ドライブで増えた分を戻す（飽和で潰れた分は戻さない）
+        y / gain.sqrt()
+    }
+
+    fn reset(&mut self) {
+        self.s = [0.0; 4];
+    }
+
+    fn response(&self, sr: f32, hz: f32) -> Complex {
+        // tanh を線形とみなした応答: comp(k) √gain L^4 / (1 + k L^4)
+        let g = (std::f32::consts::PI * self.cutoff / sr).tan();
+        let l4 = (Complex::ONE / (Complex::ONE + blt_s(sr, hz, g))).powi(4);
+        let gain = 1.0 + 9.0 * self.drive;
+        l4 * (comp(self.k) * gain.sqrt()) / (Complex::ONE + l4 * self.k)
+    }
+}
+
+/// レゾナンスによる低域の減りを補償する入力ゲイン
+#[inline]
+fn comp(k: f32) -> f32 {
+    1.0 + 0.5 * k
+}
@@ -0,0 +1,72 @@
+use crate::synth::filter::{
+    FilterTrait,
+    response::{Complex, z_inv},
+};
+
+pub const MAX_STAGES: u8 = 12;
+
+/// 1次オールパスを直列につないだフェイザー型フィルタ
+/// 原音と混ぜることで stages / 2 個のノッチができる
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Phaser {
+    cutoff: f32,
+    feedback: f32, // -0.95〜0.95
+    stages: u8,    // 2〜MAX_STAGES
+    a: f32,        // オールパス係数
+    x1: [f32; MAX_STAGES as usize],
+    y1: [f32; MAX_STAGES as usize],
+    last: f32, // 最終段の出力（フィードバック用）
+}
+
+impl Phaser {
+    pub fn new(sr: f32, cutoff: f32, feedback: f32, stages: u8) -> Self {
+        let mut f = Self::default();
+        f.set_params(sr, cutoff, feedback, stages);
+        f
+    }
+
+    pub fn set_params(&mut self, sr: f32, cutoff: f32, feedback: f32, stages: u8) {
+        self.feedback = feedback.clamp(-0.95, 0.95);
+        self.stages = stages.clamp(2, MAX_STAGES);
+        self.set_cutoff(sr, cutoff);
+    }
+
+    /// オールパスの位相が 90° 回る周波数（毎サンプル呼んでよい）
+    #[inline]
+    pub fn set_cutoff(&mut self, sr: f32, cutoff: f32) {
+        self.cutoff = cutoff.clamp(1.0, 0.49 * sr);
+        let t = (std::f32::consts::PI * self.cutoff / sr).tan();
+        self.a = (t - 1.0) / (t + 1.0);
+    }
+}
+
+impl FilterTrait for Phaser {
+    fn process(&mut self, input: f32) -> f32 {
+        let mut x = input + self.feedback * self.last;
+        let n = self.stages as usize;
+        for (x1, y1) in self.x1[..n].iter_mut().zip(self.y1[..n].iter_mut()) {
+            let y = self.a * x + *x1 - self.a * *y1;
+            *x1 = x;
+            *y1 = y;
+            x = y;
+        }
+        // デノーマル対策
+        self.last = if x.abs() < 1.0e-20 { 0.0 } else { x };
+        0.5 * (input + x)
+    }
+
+    fn reset(&mut self) {
+        self.x1 = [0.0; MAX_STAGES as usize];
+        self.y1 = [0.0; MAX_STAGES as usize];
+        self.last = 0.0;
+    }
+
+    fn response(&self, sr: f32, hz: f32) -> Complex {
+        let z1 = z_inv(sr, hz);
+        let a = Complex::new(self.a, 0.0);
+        let ap = ((a + z1) / (Complex::ONE + a * z1)).powi(self.stages as u32);
+        // フィードバックは前のサンプルの出力なので z^-1 が1つ入る
+        let wet = ap / (Complex::ONE - z1 * ap * self.feedback);
+        (Complex::ONE + wet) * 0.5
+    }
+}
@@ -0,0 +1,188 @@
+use std::ops::{Add, Div, Mul, Sub};
+
+/// 周波数応答の計算用の複素数
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const ONE: Complex = Complex { re: 1.0, im: 0.0 };
+
+    pub fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    pub fn from_polar(r: f32, theta: f32) -> Self {
+        let (s, c) = theta.sin_cos();
+        Self::new(r * c, r * s)
+    }
+
+    /// 振幅
+    pub fn abs(self) -> f32 {
+        self.re.hypot(self.im)
+    }
+
+    /// 位相 [rad]
+    pub fn arg(self) -> f32 {
+        self.im.atan2(self.re)
+    }
+
+    pub fn powi(self, n: u32) -> Self {
+        (0..n).fold(Self::ONE, |acc, _| acc * self)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, o: Self) -> Self {
+        Self::new(self.re + o.re, self.im + o.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, o: Self) -> Self {
+        Self::new(self.re - o.re, self.im - o.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, o: Self) -> Self {
+        Self::new(
+            self.re * o.re - self.im * o.im,
+            self.re * o.im + self.im * o.re,
+        )
+    }
+}
+
+impl Mul<f32> for Complex {
+    type Output = Self;
+    fn mul(self, k: f32) -> Self {
+        Self::new(self.re * k, self.im * k)
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, o: Self) -> Self {
+        let d = o.re * o.re + o.im * o.im;
+        Self::new(
+            (self.re * o.re + self.im * o.im) / d,
+            (self.im * o.re - self.re * o.im) / d,
+        )
+    }
+}
+
+/// z^-1 = e^{-jω}
+#[inline]
+pub fn z_inv(sr: f32, hz: f32) -> Complex {
+    Complex::from_polar(1.0, -std::f32::consts::TAU * hz / sr)
+}
+
+/// 双一次変換した s（カットオフで正規化。g = tan(π fc / sr)）
+/// TPT のフィルタはアナログの伝達関数にこれを代入したものと一致する
+#[inline]
+pub fn blt_s(sr: f32, hz: f32, g: f32) -> Complex {
+    let zi = z_inv(sr, hz);
+    (Complex::ONE - zi) / (Complex::ONE + zi) * g.recip()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_1_SQRT_2, TAU};
+
+    use crate::synth::filter::{
+        Biquad, BiquadMode, Comb, CombLine, CombMode, FilterTrait, OnePoleLpf, Svf,
+    };
+
+    const SR: f32 = 48000.0;
+
+    /// 正弦波を通して定常状態の振幅比を測る（直交成分との相関で求める）
+    fn measured_gain(f: &mut impl FilterTrait, hz: f32) -> f32 {
+        let settle = SR as usize;
+        let n = SR as usize;
+        let (mut re, mut im) = (0.0f64, 0.0f64);
+        for i in 0..settle + n {
+            let w = TAU * hz * i as f32 / SR;
+            let y = f.process(w.sin());
+            if i >= settle {
+                re += (y * w.sin()) as f64;
+                im += (y * w.cos()) as f64;
+            }
+        }
+        (2.0 * (re * re + im * im).sqrt() / n as f64) as f32
+    }
+
+    #[test]
+    fn biquad_lowpass_is_minus_3db_at_cutoff() {
+        let f = Biquad::new(SR, BiquadMode::Lowpass, 1000.0, FRAC_1_SQRT_2, 0.0);
+        assert!((f.magnitude_db(SR, 1000.0) + 3.0103).abs() < 0.01);
+        assert!(f.magnitude_db(SR, 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn biquad_notch_rejects_center() {
+        let f = Biquad::new(SR, BiquadMode::Notch, 2000.0, 2.0, 0.0);
+        assert!(f.magnitude_db(SR, 2000.0) < -80.0);
+        assert!(f.magnitude_db(SR, 100.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn biquad_peaking_gain_at_center() {
+        let f = Biquad::new(SR, BiquadMode::Peaking, 3000.0, 1.0, 6.0);
+        assert!((f.magnitude_db(SR, 3000.0) - 6.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn one_pole_lowpass_has_unity_dc_gain() {
+        let f = OnePoleLpf::new(SR, 500.0);
+        assert!((f.response(SR, 0.0).abs() - 1.0).abs() < 1e-4);
+        assert!(f.phase(SR, 0.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn svf_response_matches_processed_sine() {
+        for morph in [0.0, 0.5, 1.0] {
+            for hz in [200.0, 1000.0, 5000.0] {
+                let mut f = Svf::new(SR, 1000.0, 2.0, morph);
+                let expected = f.response(SR, hz).abs();
+                let measured = measured_gain(&mut f, hz);
+                assert!(
+                    (measured - expected).abs() < 1e-3 * expected.max(1.0),
+                    "morph {morph} at {hz} Hz: measured {measured}, expected {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn biquad_response_matches_processed_sine() {
+        let mut f = Biquad::new(SR, BiquadMode::HighShelf, 4000.0, FRAC_1_SQRT_2, -9.0);
+        for hz in [300.0, 4000.0, 12000.0] {
+            let expected = f.response(SR, hz).abs();
+            let measured = measured_gain(&mut f, hz);
+            assert!(
+                (measured - expected).abs() < 1e-3,
+                "{hz} Hz: {measured} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn comb_delay_follows_low_notes() {
+        // MIDI ノート 0 付近でも遅延が周期どおり（固定長の遅延線で丸められない）
+        let hz = 8.1758;
+        let mut line = CombLine::new(SR);
+        let mut f = Comb::new(SR, hz, 0.5, CombMode::FeedForward);
+        let out: Vec<f32> = (0..SR as usize)
+            .map(|i| f.process(&mut line, if i == 0 { 1.0 } else { 0.0 }))
+            .collect();
+        let echo = (1..out.len())
+            .max_by(|&a, &b| out[a].total_cmp(&out[b]))
+            .unwrap();
+        assert_eq!(echo, (SR / hz).round() as usize);
+    }
+}
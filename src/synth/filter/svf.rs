@@ -0,0 +1,102 @@
+use crate::synth::filter::{
+    FilterTrait,
+    response::{Complex, blt_s},
+};
+
+/// LP/BP/HP の同時出力
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SvfOutputs {
+    pub lp: f32,
+    pub bp: f32, // ピークゲイン 0dB に正規化済み
+    pub hp: f32,
+}
+
+/// TPT（ゼロディレイフィードバック）型ステートバリアブルフィルタ
+/// 状態が積分器の値そのものなので、毎サンプル係数を変えても破綻しない
+#[derive(Clone, Copy, Default)]
+pub struct Svf {
+    cutoff: f32,
+    q: f32,
+    morph: f32, // 0.0 = LP, 0.5 = BP, 1.0 = HP
+    k: f32,     // 1 / Q
+    a1: f32,
+    a2: f32,
+    a3: f32,
+    ic1eq: f32, // 積分器の状態
+    ic2eq: f32,
+}
+
+impl Svf {
+    pub fn new(sr: f32, cutoff: f32, q: f32, morph: f32) -> Self {
+        let mut f = Self::default();
+        f.set_params(sr, cutoff, q, morph);
+        f
+    }
+
+    pub fn set_params(&mut self, sr: f32, cutoff: f32, q: f32, morph: f32) {
+        self.q = q.max(1e-3);
+        self.k = 1.0 / self.q;
+        self.morph = morph.clamp(0.0, 1.0);
+        self.set_cutoff(sr, cutoff);
+    }
+
+    /// カットオフだけを更新する（毎サンプル呼んでよい）
+    #[inline]
+    pub fn set_cutoff(&mut self, sr: f32, cutoff: f32) {
+        self.cutoff = cutoff.clamp(1.0, 0.49 * sr);
+        let g = (std::f32::consts::PI * self.cutoff / sr).tan();
+        self.a1 = 1.0 / (1.0 + g * (g + self.k));
+        self.a2 = g * self.a1;
+        self.a3 = g * self.a2;
+    }
+
+    #[inline]
+    pub fn process_multi(&mut self, input: f32) -> SvfOutputs {
+        let v3 = input - self.ic2eq;
+        let v1 = self.a1 * self.ic1eq + self.a2 * v3;
+        let v2 = self.ic2eq + self.a2 * self.ic1eq + self.a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+        SvfOutputs {
+            lp: v2,
+            bp: self.k * v1,
+            hp: input - self.k * v1 - v2,
+        }
+    }
+
+    /// process_multi の各出力の周波数応答 [LP, BP, HP]
+    pub fn response_multi(&self, sr: f32, hz: f32) -> [Complex; 3] {
+        let g = (std::f32::consts::PI * self.cutoff / sr).tan();
+        let s = blt_s(sr, hz, g);
+        let den = s * s + s * self.k + Complex::ONE;
+        [Complex::ONE / den, s * self.k / den, s * s / den]
+    }
+}
+
+impl FilterTrait for Svf {
+    fn process(&mut self, input: f32) -> f32 {
+        let out = self.process_multi(input);
+        // LP → BP → HP を連続的にクロスフェード
+        let m = self.morph * 2.0;
+        if m < 1.0 {
+            out.lp + (out.bp - out.lp) * m
+        } else {
+            out.bp + (out.hp - out.bp) * (m - 1.0)
+        }
+    }
+
+    fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+
+    fn response(&self, sr: f32, hz: f32) -> Complex {
+        let [lp, bp, hp] = self.response_multi(sr, hz);
+        let m = self.morph * 2.0;
+        if m < 1.0 {
+            lp + (bp - lp) * m
+        } else {
+            bp + (hp - bp) * (m - 1.0)
+        }
+    }
+}
@@ -0,0 +1,315 @@
+mod delay;
+mod distortion;
+mod modulation;
+mod oversample;
+mod reverb;
+
+pub use delay::{Delay, DelayParams, DelayTime, Feel, MAX_DELAY_SEC, NoteDiv};
+pub use distortion::{Crusher, CrusherParams, Distortion, DistortionParams, ShapeCurve};
+pub use modulation::{
+    Chorus, ChorusParams, Flanger, FlangerParams, MAX_CHORUS_VOICES, Phaser, PhaserParams,
+};
+pub use oversample::Oversample;
+pub use reverb::{MAX_PRE_DELAY_MS, Reverb, ReverbParams};
+
+use crate::synth::smooth::Smoothed;
+
+pub const MAX_FX: usize = 8;
+const ORDER_FADE: u32 = 256; // 処理順の入れ替えにかける最短のサンプル数（48kHz で約 5ms）
+
+/// エフェクトの種類とパラメータ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FxType {
+    Gain(f32), // 音量[dB]
+    Delay(DelayParams),
+    Reverb(ReverbParams),
+    Chorus(ChorusParams),
+    Flanger(FlangerParams),
+    Phaser(PhaserParams),
+    Distortion(DistortionParams),
+    Crusher(CrusherParams),
+}
+
+pub trait EffectTrait {
+    /// 1フレーム [L, R] を処理する（ウェットのみを返す）
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2];
+    fn reset(&mut self);
+    /// テンポ同期するものはテンポ [BPM] を受け取る
+    fn set_tempo(&mut self, _bpm: f32) {}
+}
+
+/// エフェクト本体
+/// バッファを確保するものもあるので、種類を変えるときは UI スレッドで作って送る
+#[derive(Debug)]
+pub enum Effect {
+    Gain(Gain),
+    Delay(Delay),
+    Reverb(Reverb),
+    Chorus(Chorus),
+    Flanger(Flanger),
+    Phaser(Phaser),
+    Distortion(Distortion),
+    Crusher(Crusher),
+}
+
+impl Effect {
+    pub fn new(ft: FxType, sr: f32) -> Self {
+        match ft {
+            FxType::Gain(db) => Effect::Gain(Gain::new(sr, db)),
+            FxType::Delay(p) => Effect::Delay(Delay::new(sr, p)),
+            FxType::Reverb(p) => Effect::Reverb(Reverb::new(sr, p)),
+            FxType::Chorus(p) => Effect::Chorus(Chorus::new(sr, p)),
+            FxType::Flanger(p) => Effect::Flanger(Flanger::new(sr, p)),
+            FxType::Phaser(p) => Effect::Phaser(Phaser::new(sr, p)),
+            FxType::Distortion(p) => Effect::Distortion(Distortion::new(sr, p)),
+            FxType::Crusher(p) => Effect::Crusher(Crusher::new(sr, p)),
+        }
+    }
+
+    /// 同じ種類ならパラメータだけ更新する（状態はそのまま）。種類が違えば false
+    pub fn set_params(&mut self, ft: FxType) -> bool {
+        match (self, ft) {
+            (Effect::Gain(g), FxType::Gain(db)) => g.set_db(db),
+            (Effect::Delay(d), FxType::Delay(p)) => d.set_params(p),
+            (Effect::Reverb(r), FxType::Reverb(p)) => r.set_params(p),
+            (Effect::Chorus(c), FxType::Chorus(p)) => c.set_params(p),
+            (Effect::Flanger(f), FxType::Flanger(p)) => f.set_params(p),
+            (Effect::Phaser(ph), FxType::Phaser(p)) => ph.set_params(p),
+            (Effect::Distortion(d), FxType::Distortion(p)) => d.set_params(p),
+            (Effect::Crusher(c), FxType::Crusher(p)) => c.set_params(p),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl EffectTrait for Effect {
+    #[inline]
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        match self {
+            Effect::Gain(g) => g.process(frame),
+            Effect::Delay(d) => d.process(frame),
+            Effect::Reverb(r) => r.process(frame),
+            Effect::Chorus(c) => c.process(frame),
+            Effect::Flanger(f) => f.process(frame),
+            Effect::Phaser(p) => p.process(frame),
+            Effect::Distortion(d) => d.process(frame),
+            Effect::Crusher(c) => c.process(frame),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Effect::Gain(g) => g.reset(),
+            Effect::Delay(d) => d.reset(),
+            Effect::Reverb(r) => r.reset(),
+            Effect::Chorus(c) => c.reset(),
+            Effect::Flanger(f) => f.reset(),
+            Effect::Phaser(p) => p.reset(),
+            Effect::Distortion(d) => d.reset(),
+            Effect::Crusher(c) => c.reset(),
+        }
+    }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        match self {
+            Effect::Gain(g) => g.set_tempo(bpm),
+            Effect::Delay(d) => d.set_tempo(bpm),
+            Effect::Reverb(r) => r.set_tempo(bpm),
+            Effect::Chorus(c) => c.set_tempo(bpm),
+            Effect::Flanger(f) => f.set_tempo(bpm),
+            Effect::Phaser(p) => p.set_tempo(bpm),
+            Effect::Distortion(d) => d.set_tempo(bpm),
+            Effect::Crusher(c) => c.set_tempo(bpm),
+        }
+    }
+}
+
+/// 音量を変えるだけのエフェクト
+#[derive(Debug, Clone, Copy)]
+pub struct Gain {
+    gain: Smoothed,
+    ramp: u32, // 音量の変化にかけるサンプル数
+}
+
+impl Gain {
+    pub fn new(sr: f32, db: f32) -> Self {
+        Self {
+            gain: Smoothed::new(db_to_gain(db)),
+            ramp: (0.02 * sr) as u32,
+        }
+    }
+
+    pub fn set_db(&mut self, db: f32) {
+        self.gain.set_target(db_to_gain(db), self.ramp);
+    }
+}
+
+impl EffectTrait for Gain {
+    #[inline]
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let g = self.gain.next();
+        frame.map(|x| x * g)
+    }
+
+    fn reset(&mut self) {}
+}
+
+#[inline]
+pub fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[derive(Debug, Default)]
+struct FxSlot {
+    effect: Option<Box<Effect>>,
+    mix: f32, // 0.0（ドライ）〜 1.0（ウェット）
+    bypass: bool,
+    wet: Smoothed, // 実際に掛けているウェットの割合（バイパス時は 0 へ向かう）
+}
+
+impl FxSlot {
+    fn wet_target(&self) -> f32 {
+        if self.bypass { 0.0 } else { self.mix }
+    }
+}
+
+/// ボイスのミックスの後ろに挿す直列のエフェクトチェーン
+#[derive(Debug)]
+pub struct FxChain {
+    slots: [FxSlot; MAX_FX],
+    order: [u8; MAX_FX],              // 処理する順のスロット番号
+    next_order: Option<[u8; MAX_FX]>, // 出力をドライへ絞り切ったら切り替える処理順
+    order_fade: Smoothed,             // チェーンの出力の割合（1.0 = チェーン、0.0 = ドライ）
+    fade_samples: u32,
+    bpm: f32,
+}
+
+impl Default for FxChain {
+    fn default() -> Self {
+        Self {
+            slots: std::array::from_fn(|_| FxSlot {
+                mix: 1.0,
+                wet: Smoothed::new(1.0),
+                ..Default::default()
+            }),
+            order: std::array::from_fn(|i| i as u8),
+            next_order: None,
+            order_fade: Smoothed::new(1.0),
+            fade_samples: ORDER_FADE,
+            bpm: 120.0,
+        }
+    }
+}
+
+impl FxChain {
+    /// スロットのエフェクトを差し替え、古いものを返す（UI スレッドで解放するため）
+    /// スロット番号が範囲外なら受け取ったものをそのまま返す
+    pub fn load(&mut self, slot: usize, mut effect: Option<Box<Effect>>) -> Option<Box<Effect>> {
+        let Some(s) = self.slots.get_mut(slot) else {
+            return effect;
+        };
+        if let Some(fx) = effect.as_mut() {
+            fx.set_tempo(self.bpm);
+        }
+        s.wet = Smoothed::new(s.wet_target());
+        std::mem::replace(&mut s.effect, effect)
+    }
+
+    /// 読み込み済みのエフェクトのパラメータを変える（種類が違えば無視）
+    pub fn set_params(&mut self, slot: usize, ft: FxType) {
+        if let Some(fx) = self.slots.get_mut(slot).and_then(|s| s.effect.as_mut()) {
+            fx.set_params(ft);
+        }
+    }
+
+    pub fn set_mix(&mut self, slot: usize, mix: f32, samples: u32) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            s.mix = mix.clamp(0.0, 1.0);
+            s.wet.set_target(s.wet_target(), samples);
+        }
+    }
+
+    pub fn set_bypass(&mut self, slot: usize, bypass: bool, samples: u32) {
+        let Some(s) = self.slots.get_mut(slot) else {
+            return;
+        };
+        // 止まっていたエフェクトは古い状態を鳴らさないよう空にしてから戻す
+        if s.bypass
+            && !bypass
+            && !s.wet.is_ramping()
+            && let Some(fx) = s.effect.as_mut()
+        {
+            fx.reset();
+        }
+        s.bypass = bypass;
+        s.wet.set_target(s.wet_target(), samples);
+    }
+
+    pub fn set_tempo(&mut self, bpm: f32) {
+        self.bpm = bpm.clamp(20.0, 400.0);
+        for fx in self.slots.iter_mut().filter_map(|s| s.effect.as_mut()) {
+            fx.set_tempo(self.bpm);
+        }
+    }
+
+    /// 処理順を入れ替える（0..MAX_FX の並べ替えになっていなければ無視）
+    /// エフェクトは状態を持つので2通りの順で同時には鳴らせない。
+    /// samples かけて出力をドライへ絞り、切り替えてから戻す
+    pub fn set_order(&mut self, order: [u8; MAX_FX], samples: u32) {
+        let mut seen = [false; MAX_FX];
+        for &i in &order {
+            match seen.get_mut(i as usize) {
+                Some(s) if !*s => *s = true,
+                _ => return,
+            }
+        }
+        if order == self.order && self.next_order.is_none() {
+            return;
+        }
+        self.fade_samples = samples.max(ORDER_FADE);
+        self.next_order = Some(order);
+        self.order_fade.set_target(0.0, self.fade_samples);
+    }
+
+    /// すべてのエフェクトの状態（残響など）を消す
+    pub fn reset(&mut self) {
+        for fx in self.slots.iter_mut().filter_map(|s| s.effect.as_mut()) {
+            fx.reset();
+        }
+        if let Some(order) = self.next_order.take() {
+            self.order = order;
+        }
+        self.order_fade = Smoothed::new(1.0);
+    }
+
+    #[inline]
+    pub fn process(&mut self, mut frame: [f32; 2]) -> [f32; 2] {
+        let dry = frame;
+        for &i in &self.order {
+            let slot = &mut self.slots[i as usize];
+            let Some(fx) = slot.effect.as_mut() else {
+                continue;
+            };
+            let wet = slot.wet.next();
+            // バイパスし終わったものは処理しない
+            if wet == 0.0 && !slot.wet.is_ramping() {
+                continue;
+            }
+            let out = fx.process(frame);
+            frame = [0, 1].map(|ch| frame[ch] + (out[ch] - frame[ch]) * wet);
+        }
+        let fade = self.order_fade.next();
+        if let Some(order) = self.next_order
+            && !self.order_fade.is_ramping()
+        {
+            self.order = order;
+            self.next_order = None;
+            self.order_fade.set_target(1.0, self.fade_samples);
+        }
+        if fade < 1.0 {
+            frame = [0, 1].map(|ch| dry[ch] + (frame[ch] - dry[ch]) * fade);
+        }
+        frame
+    }
+}
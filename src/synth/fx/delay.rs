@@ -0,0 +1,189 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use crate::synth::{
+    filter::{FilterTrait, OnePoleLpf},
+    fx::EffectTrait,
+};
+
+pub const MAX_DELAY_SEC: f32 = 4.0;
+const MAX_MOD_MS: f32 = 20.0;
+
+/// 音符の長さ（テンポ同期用）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteDiv {
+    pub denom: u8, // 1, 2, 4, 8, 16, 32 分音符
+    pub feel: Feel,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Feel {
+    #[default]
+    Straight,
+    Dotted,
+    Triplet,
+}
+
+impl NoteDiv {
+    /// 4分音符を 1 とした長さ
+    pub fn beats(self) -> f32 {
+        let base = 4.0 / self.denom.max(1) as f32;
+        match self.feel {
+            Feel::Straight => base,
+            Feel::Dotted => base * 1.5,
+            Feel::Triplet => base * 2.0 / 3.0,
+        }
+    }
+
+    pub fn seconds(self, bpm: f32) -> f32 {
+        self.beats() * 60.0 / bpm.max(1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DelayTime {
+    Ms(f32),
+    Sync(NoteDiv),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DelayParams {
+    pub time: DelayTime,
+    pub feedback: f32,   // 0.0〜0.95
+    pub low_cut: f32,    // フィードバックに掛けるハイパス [Hz]
+    pub high_cut: f32,   // フィードバックに掛けるローパス [Hz]
+    pub ping_pong: bool, // 左右交互に跳ね返す
+    pub mod_rate: f32,   // ディレイタイムを揺らす LFO [Hz]
+    pub mod_depth: f32,  // 揺らす幅 [ms]
+}
+
+impl Default for DelayParams {
+    fn default() -> Self {
+        Self {
+            time: DelayTime::Sync(NoteDiv {
+                denom: 8,
+                feel: Feel::Dotted,
+            }),
+            feedback: 0.4,
+            low_cut: 100.0,
+            high_cut: 6000.0,
+            ping_pong: false,
+            mod_rate: 0.5,
+            mod_depth: 0.0,
+        }
+    }
+}
+
+/// テンポ同期できるステレオディレイ
+/// バッファは作るときに最大時間ぶん確保し、時間を変えても確保し直さない
+#[derive(Debug)]
+pub struct Delay {
+    sr: f32,
+    params: DelayParams,
+    bpm: f32,
+    buf: Vec<[f32; 2]>,
+    pos: usize,
+    target: f32, // 目標のディレイタイム [サンプル]
+    time: f32,   // 実際のディレイタイム（テープのように滑らかに追いかける）
+    glide: f32,
+    lfo_phase: f32,
+    lp: [OnePoleLpf; 2],
+    hp: [OnePoleLpf; 2], // low_cut の LPF（元の信号から引いてハイパスにする）
+}
+
+impl Delay {
+    pub fn new(sr: f32, params: DelayParams) -> Self {
+        let len = ((MAX_DELAY_SEC + MAX_MOD_MS * 0.001) * sr) as usize + 4;
+        let mut d = Self {
+            sr,
+            params,
+            bpm: 120.0,
+            buf: vec![[0.0; 2]; len],
+            pos: 0,
+            target: 1.0,
+            time: 1.0,
+            glide: 1.0 - (-1.0 / (0.05 * sr)).exp(),
+            lfo_phase: 0.0,
+            lp: [OnePoleLpf::new(sr, params.high_cut); 2],
+            hp: [OnePoleLpf::new(sr, params.low_cut); 2],
+        };
+        d.set_params(params);
+        d.time = d.target;
+        d
+    }
+
+    pub fn set_params(&mut self, params: DelayParams) {
+        self.params = params;
+        for f in self.lp.iter_mut() {
+            f.set_cutoff(self.sr, params.high_cut);
+        }
+        for f in self.hp.iter_mut() {
+            f.set_cutoff(self.sr, params.low_cut);
+        }
+        self.update_time();
+    }
+
+    fn update_time(&mut self) {
+        let sec = match self.params.time {
+            DelayTime::Ms(ms) => ms * 0.001,
+            DelayTime::Sync(div) => div.seconds(self.bpm),
+        };
+        self.target = sec.clamp(0.001, MAX_DELAY_SEC) * self.sr;
+    }
+
+    /// delay サンプル前の値（線形補間）
+    #[inline]
+    fn tap(&self, ch: usize, delay: f32) -> f32 {
+        let len = self.buf.len();
+        let d = delay.floor() as usize;
+        let t = delay - d as f32;
+        let i0 = (self.pos + len - d) % len;
+        let i1 = (i0 + len - 1) % len;
+        self.buf[i0][ch] + (self.buf[i1][ch] - self.buf[i0][ch]) * t
+    }
+}
+
+impl EffectTrait for Delay {
+    fn process(&mut self, [l, r]: [f32; 2]) -> [f32; 2] {
+        let p = self.params;
+        self.time += (self.target - self.time) * self.glide;
+        let depth = p.mod_depth.clamp(0.0, MAX_MOD_MS) * 0.001 * self.sr;
+        let max = (self.buf.len() - 3) as f32;
+        // 左右の LFO を 90° ずらして広がりを出す
+        let y = [0.0, FRAC_PI_2].map(|offset| {
+            let m = (self.lfo_phase * TAU + offset).sin() * 0.5 + 0.5;
+            (self.time + m * depth).clamp(1.0, max)
+        });
+        let y = [self.tap(0, y[0]), self.tap(1, y[1])];
+        self.lfo_phase = (self.lfo_phase + p.mod_rate / self.sr).fract();
+
+        let fb = p.feedback.clamp(0.0, 0.95);
+        let mut back = [0.0; 2];
+        for ch in 0..2 {
+            let x = self.lp[ch].process(y[ch]);
+            back[ch] = (x - self.hp[ch].process(x)) * fb;
+        }
+        let w = if p.ping_pong {
+            // 入力は左だけに入れ、跳ね返りは反対側へ送る
+            [0.5 * (l + r) + back[1], back[0]]
+        } else {
+            [l + back[0], r + back[1]]
+        };
+        // デノーマル対策
+        self.buf[self.pos] = w.map(|x| if x.abs() < 1.0e-20 { 0.0 } else { x });
+        self.pos = (self.pos + 1) % self.buf.len();
+        y
+    }
+
+    fn reset(&mut self) {
+        self.buf.fill([0.0; 2]);
+        for f in self.lp.iter_mut().chain(self.hp.iter_mut()) {
+            f.reset();
+        }
+        self.time = self.target;
+    }
+
+    fn set_tempo(&mut self, bpm: f32) {
+        self.bpm = bpm;
+        self.update_time();
+    }
+}
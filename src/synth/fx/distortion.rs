@@ -0,0 +1,186 @@
+use crate::synth::{
+    filter::{Biquad, BiquadMode, FilterTrait, OnePoleLpf},
+    fx::{
+        EffectTrait, db_to_gain,
+        oversample::{Oversample, Oversampler},
+    },
+};
+
+const TUBE_BIAS: f32 = 0.3;
+const DC_CUTOFF: f32 = 10.0;
+const Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// 歪みのカーブ
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ShapeCurve {
+    #[default]
+    Tanh,
+    HardClip,
+    Foldback, // ±1 を超えた分を折り返す
+    Tube,     // 正負で非対称（偶数次倍音が出る）
+}
+
+impl ShapeCurve {
+    #[inline]
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            ShapeCurve::Tanh => x.tanh(),
+            ShapeCurve::HardClip => x.clamp(-1.0, 1.0),
+            ShapeCurve::Foldback => 1.0 - ((x + 1.0).rem_euclid(4.0) - 2.0).abs(),
+            ShapeCurve::Tube => (x + TUBE_BIAS).tanh() - TUBE_BIAS.tanh(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DistortionParams {
+    pub curve: ShapeCurve,
+    pub drive: f32,   // 入力ゲイン [dB]
+    pub low_cut: f32, // 歪ませる前のハイパス [Hz]
+    pub tone: f32,    // 歪ませた後のローパス [Hz]
+    pub output: f32,  // 出力ゲイン [dB]
+    pub oversample: Oversample,
+}
+
+impl Default for DistortionParams {
+    fn default() -> Self {
+        Self {
+            curve: ShapeCurve::Tanh,
+            drive: 12.0,
+            low_cut: 80.0,
+            tone: 8000.0,
+            output: -6.0,
+            oversample: Oversample::X2,
+        }
+    }
+}
+
+/// 前後にトーンフィルタを持つウェーブシェイパー
+#[derive(Debug)]
+pub struct Distortion {
+    sr: f32,
+    params: DistortionParams,
+    drive: f32,
+    output: f32,
+    pre: [Biquad; 2],
+    post: [Biquad; 2],
+    dc: [OnePoleLpf; 2], // 非対称カーブで出る直流を取り除く（引き算でハイパスにする）
+    os: Box<[Oversampler; 2]>, // 大きいので他のエフェクトと同じくヒープに置く
+}
+
+impl Distortion {
+    pub fn new(sr: f32, params: DistortionParams) -> Self {
+        let mut d = Self {
+            sr,
+            params,
+            drive: 1.0,
+            output: 1.0,
+            pre: [Biquad::new(sr, BiquadMode::Highpass, params.low_cut, Q, 0.0); 2],
+            post: [Biquad::new(sr, BiquadMode::Lowpass, params.tone, Q, 0.0); 2],
+            dc: [OnePoleLpf::new(sr, DC_CUTOFF); 2],
+            os: Default::default(),
+        };
+        d.set_params(params);
+        d
+    }
+
+    pub fn set_params(&mut self, params: DistortionParams) {
+        if params.oversample != self.params.oversample {
+            for os in self.os.iter_mut() {
+                os.reset();
+            }
+        }
+        self.params = params;
+        self.drive = db_to_gain(params.drive);
+        self.output = db_to_gain(params.output);
+        for f in self.pre.iter_mut() {
+            f.set_params(self.sr, BiquadMode::Highpass, params.low_cut, Q, 0.0);
+        }
+        for f in self.post.iter_mut() {
+            f.set_params(self.sr, BiquadMode::Lowpass, params.tone, Q, 0.0);
+        }
+    }
+}
+
+impl EffectTrait for Distortion {
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let DistortionParams {
+            curve, oversample, ..
+        } = self.params;
+        let mut out = [0.0; 2];
+        for ch in 0..2 {
+            let x = self.pre[ch].process(frame[ch]) * self.drive;
+            let y = self.os[ch].process(oversample, x, |x| curve.apply(x));
+            let y = y - self.dc[ch].process(y);
+            out[ch] = self.post[ch].process(y) * self.output;
+        }
+        out
+    }
+
+    fn reset(&mut self) {
+        for ch in 0..2 {
+            self.pre[ch].reset();
+            self.post[ch].reset();
+            self.dc[ch].reset();
+            self.os[ch].reset();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrusherParams {
+    pub bits: f32, // 量子化ビット数 1〜16（小数も可）
+    pub rate: f32, // サンプル＆ホールドの周波数 [Hz]
+}
+
+impl Default for CrusherParams {
+    fn default() -> Self {
+        Self {
+            bits: 8.0,
+            rate: 8000.0,
+        }
+    }
+}
+
+/// ビット数とサンプルレートを落とすローファイエフェクト
+#[derive(Debug)]
+pub struct Crusher {
+    sr: f32,
+    params: CrusherParams,
+    phase: f32,
+    hold: [f32; 2],
+}
+
+impl Crusher {
+    pub fn new(sr: f32, params: CrusherParams) -> Self {
+        Self {
+            sr,
+            params,
+            phase: 1.0,
+            hold: [0.0; 2],
+        }
+    }
+
+    pub fn set_params(&mut self, params: CrusherParams) {
+        self.params = params;
+    }
+}
+
+impl EffectTrait for Crusher {
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let p = self.params;
+        // rate ごとに新しい値を取り込み、それまでは保持する
+        self.phase += p.rate.clamp(1.0, self.sr) / self.sr;
+        if self.phase >= 1.0 {
+            self.phase -= 1.0;
+            let levels = 2f32.powf(p.bits.clamp(1.0, 16.0) - 1.0);
+            self.hold = frame.map(|x| (x * levels).round() / levels);
+        }
+        self.hold
+    }
+
+    fn reset(&mut self) {
+        self.phase = 1.0;
+        self.hold = [0.0; 2];
+    }
+}
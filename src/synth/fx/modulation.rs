@@ -0,0 +1,281 @@
+use std::f32::consts::TAU;
+
+use crate::synth::{
+    filter::{FilterTrait, MAX_STAGES, Phaser as PhaserFilter},
+    fx::EffectTrait,
+};
+
+pub const MAX_CHORUS_VOICES: u8 = 4;
+const CHORUS_MAX_MS: f32 = 40.0 + 20.0; // 基準ディレイ + 揺れ幅の上限
+const FLANGER_MAX_MS: f32 = 10.0 + 10.0;
+
+/// 右チャンネルの LFO を spread * 90° ずらした位相（0.0〜1.0）
+#[inline]
+fn lfo_phases(phase: f32, spread: f32) -> [f32; 2] {
+    [phase, (phase + 0.25 * spread).fract()]
+}
+
+/// LFO の値（0.0〜1.0）
+#[inline]
+fn lfo(phase: f32) -> f32 {
+    0.5 + 0.5 * (phase * TAU).sin()
+}
+
+/// 小数サンプルで読み出せる遅延線
+#[derive(Debug)]
+struct ModLine {
+    buf: Vec<f32>,
+    pos: usize,
+}
+
+impl ModLine {
+    fn new(sr: f32, max_ms: f32) -> Self {
+        Self {
+            buf: vec![0.0; (max_ms * 0.001 * sr) as usize + 4],
+            pos: 0,
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, x: f32) {
+        // デノーマル対策
+        self.buf[self.pos] = if x.abs() < 1.0e-20 { 0.0 } else { x };
+        self.pos = (self.pos + 1) % self.buf.len();
+    }
+
+    /// delay サンプル前の値（線形補間、1.0 で直前に push した値）
+    #[inline]
+    fn tap(&self, delay: f32) -> f32 {
+        let len = self.buf.len();
+        let delay = delay.clamp(1.0, (len - 2) as f32);
+        let d = delay.floor() as usize;
+        let t = delay - d as f32;
+        let i0 = (self.pos + len - d) % len;
+        let i1 = (i0 + len - 1) % len;
+        self.buf[i0] + (self.buf[i1] - self.buf[i0]) * t
+    }
+
+    fn reset(&mut self) {
+        self.buf.fill(0.0);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChorusParams {
+    pub voices: u8,  // 1〜MAX_CHORUS_VOICES
+    pub rate: f32,   // LFO [Hz]
+    pub depth: f32,  // 揺れ幅 [ms]
+    pub delay: f32,  // 基準ディレイ [ms]
+    pub spread: f32, // 0.0〜1.0 左右の LFO のずれ
+}
+
+impl Default for ChorusParams {
+    fn default() -> Self {
+        Self {
+            voices: 3,
+            rate: 0.8,
+            depth: 3.0,
+            delay: 15.0,
+            spread: 1.0,
+        }
+    }
+}
+
+/// 位相をずらした LFO で揺らす複数のディレイを重ねるコーラス
+#[derive(Debug)]
+pub struct Chorus {
+    sr: f32,
+    params: ChorusParams,
+    lines: [ModLine; 2],
+    phase: f32,
+}
+
+impl Chorus {
+    pub fn new(sr: f32, params: ChorusParams) -> Self {
+        Self {
+            sr,
+            params,
+            lines: [0, 1].map(|_| ModLine::new(sr, CHORUS_MAX_MS)),
+            phase: 0.0,
+        }
+    }
+
+    pub fn set_params(&mut self, params: ChorusParams) {
+        self.params = params;
+    }
+}
+
+impl EffectTrait for Chorus {
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let p = self.params;
+        let n = p.voices.clamp(1, MAX_CHORUS_VOICES);
+        let ms = 0.001 * self.sr;
+        let base = p.delay.clamp(1.0, 40.0) * ms;
+        let depth = p.depth.clamp(0.0, 20.0) * ms;
+        let phases = lfo_phases(self.phase, p.spread);
+        self.phase = (self.phase + p.rate / self.sr).fract();
+        let mut out = [0.0; 2];
+        for ch in 0..2 {
+            self.lines[ch].push(frame[ch]);
+            for v in 0..n {
+                // ボイスごとに LFO の位相を均等にずらす
+                let phase = (phases[ch] + v as f32 / n as f32).fract();
+                out[ch] += self.lines[ch].tap(base + depth * lfo(phase));
+            }
+        }
+        out.map(|x| x / n as f32)
+    }
+
+    fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.reset();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlangerParams {
+    pub rate: f32,     // LFO [Hz]
+    pub depth: f32,    // 揺れ幅 [ms]
+    pub delay: f32,    // 基準ディレイ [ms]
+    pub feedback: f32, // -0.95〜0.95
+    pub spread: f32,   // 0.0〜1.0 左右の LFO のずれ
+}
+
+impl Default for FlangerParams {
+    fn default() -> Self {
+        Self {
+            rate: 0.2,
+            depth: 2.0,
+            delay: 1.0,
+            feedback: 0.6,
+            spread: 0.5,
+        }
+    }
+}
+
+/// 短いディレイを揺らしてフィードバックするフランジャー
+#[derive(Debug)]
+pub struct Flanger {
+    sr: f32,
+    params: FlangerParams,
+    lines: [ModLine; 2],
+    last: [f32; 2],
+    phase: f32,
+}
+
+impl Flanger {
+    pub fn new(sr: f32, params: FlangerParams) -> Self {
+        Self {
+            sr,
+            params,
+            lines: [0, 1].map(|_| ModLine::new(sr, FLANGER_MAX_MS)),
+            last: [0.0; 2],
+            phase: 0.0,
+        }
+    }
+
+    pub fn set_params(&mut self, params: FlangerParams) {
+        self.params = params;
+    }
+}
+
+impl EffectTrait for Flanger {
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let p = self.params;
+        let ms = 0.001 * self.sr;
+        let base = p.delay.clamp(0.1, 10.0) * ms;
+        let depth = p.depth.clamp(0.0, 10.0) * ms;
+        let fb = p.feedback.clamp(-0.95, 0.95);
+        let phases = lfo_phases(self.phase, p.spread);
+        self.phase = (self.phase + p.rate / self.sr).fract();
+        let mut out = [0.0; 2];
+        for ch in 0..2 {
+            let line = &mut self.lines[ch];
+            line.push(frame[ch] + fb * self.last[ch]);
+            out[ch] = line.tap(base + depth * lfo(phases[ch]));
+            self.last[ch] = out[ch];
+        }
+        out
+    }
+
+    fn reset(&mut self) {
+        for line in self.lines.iter_mut() {
+            line.reset();
+        }
+        self.last = [0.0; 2];
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhaserParams {
+    pub rate: f32,     // LFO [Hz]
+    pub depth: f32,    // 掃引幅 [オクターブ]
+    pub center: f32,   // 掃引の中心 [Hz]
+    pub feedback: f32, // -0.95〜0.95
+    pub stages: u8,    // 2〜MAX_STAGES
+    pub spread: f32,   // 0.0〜1.0 左右の LFO のずれ
+}
+
+impl Default for PhaserParams {
+    fn default() -> Self {
+        Self {
+            rate: 0.3,
+            depth: 2.0,
+            center: 800.0,
+            feedback: 0.5,
+            stages: 6,
+            spread: 0.5,
+        }
+    }
+}
+
+/// オールパスの折点を LFO で掃引するフェイザー
+#[derive(Debug)]
+pub struct Phaser {
+    sr: f32,
+    params: PhaserParams,
+    filters: [PhaserFilter; 2],
+    phase: f32,
+}
+
+impl Phaser {
+    pub fn new(sr: f32, params: PhaserParams) -> Self {
+        let f = PhaserFilter::new(sr, params.center, params.feedback, params.stages);
+        Self {
+            sr,
+            params,
+            filters: [f; 2],
+            phase: 0.0,
+        }
+    }
+
+    pub fn set_params(&mut self, params: PhaserParams) {
+        self.params = params;
+        let stages = params.stages.clamp(2, MAX_STAGES);
+        for f in self.filters.iter_mut() {
+            f.set_params(self.sr, params.center, params.feedback, stages);
+        }
+    }
+}
+
+impl EffectTrait for Phaser {
+    fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let p = self.params;
+        let phases = lfo_phases(self.phase, p.spread);
+        self.phase = (self.phase + p.rate / self.sr).fract();
+        let mut out = [0.0; 2];
+        for ch in 0..2 {
+            let oct = p.depth * (lfo(phases[ch]) - 0.5);
+            self.filters[ch].set_cutoff(self.sr, p.center * oct.exp2());
+            out[ch] = self.filters[ch].process(frame[ch]);
+        }
+        out
+    }
+
+    fn reset(&mut self) {
+        for f in self.filters.iter_mut() {
+            f.reset();
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::f32::consts::PI;
+
+const TAPS: usize = 31; // ハーフバンド FIR の長さ（奇数）
+
+/// オーバーサンプリングの倍率
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Oversample {
+    #[default]
+    X1,
+    X2,
+    X4,
+}
+
+/// 2倍のアップ / ダウンサンプリングに使うハーフバンド FIR（Blackman 窓の sinc）
+#[derive(Debug, Clone, Copy)]
+struct Halfband {
+    coefs: [f32; TAPS],
+    hist: [f32; 2 * TAPS], // 同じ値を2か所に書き、連続したスライスで畳み込む
+    pos: usize,
+}
+
+impl Halfband {
+    fn new() -> Self {
+        let mid = (TAPS / 2) as f32;
+        let mut coefs = [0.0; TAPS];
+        for (i, c) in coefs.iter_mut().enumerate() {
+            let n = i as f32 - mid;
+            let sinc = if n == 0.0 {
+                0.5
+            } else {
+                (0.5 * PI * n).sin() / (PI * n)
+            };
+            let w = i as f32 / (TAPS - 1) as f32;
+            let window = 0.42 - 0.5 * (2.0 * PI * w).cos() + 0.08 * (4.0 * PI * w).cos();
+            *c = sinc * window;
+        }
+        // DC ゲインを 1 に揃える
+        let sum: f32 = coefs.iter().sum();
+        coefs.iter_mut().for_each(|c| *c /= sum);
+        Self {
+            coefs,
+            hist: [0.0; 2 * TAPS],
+            pos: 0,
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, x: f32) {
+        self.pos = (self.pos + 1) % TAPS;
+        self.hist[self.pos] = x;
+        self.hist[self.pos + TAPS] = x;
+    }
+
+    #[inline]
+    fn output(&self) -> f32 {
+        // hist[pos + 1 .. pos + 1 + TAPS] が古い順に並んでいる
+        let h = &self.hist[self.pos + 1..self.pos + 1 + TAPS];
+        h.iter().zip(self.coefs.iter()).map(|(x, c)| x * c).sum()
+    }
+
+    /// 1サンプルを2サンプルに（ゼロを挟んで補間）
+    #[inline]
+    fn up(&mut self, x: f32) -> [f32; 2] {
+        self.push(2.0 * x);
+        let a = self.output();
+        self.push(0.0);
+        [a, self.output()]
+    }
+
+    /// 2サンプルを1サンプルに（帯域制限してから間引く）
+    #[inline]
+    fn down(&mut self, [a, b]: [f32; 2]) -> f32 {
+        self.push(a);
+        self.push(b);
+        self.output()
+    }
+
+    fn reset(&mut self) {
+        self.hist = [0.0; 2 * TAPS];
+    }
+}
+
+/// 非線形処理を高いサンプルレートで行い、折り返し雑音を減らす（1チャンネル分）
+#[derive(Debug, Clone, Copy)]
+pub struct Oversampler {
+    up: [Halfband; 2],
+    down: [Halfband; 2],
+}
+
+impl Default for Oversampler {
+    fn default() -> Self {
+        let hb = Halfband::new();
+        Self {
+            up: [hb; 2],
+            down: [hb; 2],
+        }
+    }
+}
+
+impl Oversampler {
+    #[inline]
+    pub fn process(&mut self, factor: Oversample, x: f32, mut f: impl FnMut(f32) -> f32) -> f32 {
+        match factor {
+            Oversample::X1 => f(x),
+            Oversample::X2 => {
+                let up = self.up[0].up(x).map(&mut f);
+                self.down[0].down(up)
+            }
+            Oversample::X4 => {
+                let [a, b] = self.up[0].up(x);
+                let ya = self.up[1].up(a).map(&mut f);
+                let yb = self.up[1].up(b).map(&mut f);
+                let mid = [self.down[1].down(ya), self.down[1].down(yb)];
+                self.down[0].down(mid)
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for hb in self.up.iter_mut().chain(self.down.iter_mut()) {
+            hb.reset();
+        }
+    }
+}
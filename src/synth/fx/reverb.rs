@@ -0,0 +1,185 @@
+use crate::synth::fx::EffectTrait;
+
+// Freeverb の遅延長（44.1kHz のサンプル数）。右チャンネルは STEREO_SPREAD だけずらす
+const COMB_TUNING: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+const ALLPASS_TUNING: [usize; 4] = [556, 441, 341, 225];
+const STEREO_SPREAD: usize = 23;
+const TUNING_SR: f32 = 44100.0;
+const INPUT_GAIN: f32 = 0.015;
+const DAMP_SCALE: f32 = 0.4;
+const MIN_SCALE: f32 = 0.4; // size 0.0 のときの遅延長の倍率
+const MAX_SCALE: f32 = 1.5; // size 1.0（バッファはこの長さで確保する）
+pub const MAX_PRE_DELAY_MS: f32 = 250.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReverbParams {
+    pub size: f32,      // 0.0〜1.0 部屋の大きさ（遅延長）
+    pub decay: f32,     // 残響時間 RT60 [秒]
+    pub damping: f32,   // 0.0〜1.0 高域の減衰
+    pub pre_delay: f32, // [ms]
+    pub width: f32,     // 0.0（モノラル）〜 1.0
+}
+
+impl Default for ReverbParams {
+    fn default() -> Self {
+        Self {
+            size: 0.5,
+            decay: 2.0,
+            damping: 0.5,
+            pre_delay: 10.0,
+            width: 1.0,
+        }
+    }
+}
+
+/// ローパスを挟んだフィードバックコム
+#[derive(Debug)]
+struct Comb {
+    buf: Vec<f32>,
+    pos: usize,
+    len: usize,
+    feedback: f32,
+    damp: f32,
+    store: f32,
+}
+
+impl Comb {
+    fn new(max_len: usize) -> Self {
+        Self {
+            buf: vec![0.0; max_len],
+            pos: 0,
+            len: max_len,
+            feedback: 0.0,
+            damp: 0.0,
+            store: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let cap = self.buf.len();
+        let y = self.buf[(self.pos + cap - self.len) % cap];
+        self.store = y * (1.0 - self.damp) + self.store * self.damp;
+        // デノーマル対策
+        if self.store.abs() < 1.0e-20 {
+            self.store = 0.0;
+        }
+        self.buf[self.pos] = x + self.store * self.feedback;
+        self.pos = (self.pos + 1) % cap;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.buf.fill(0.0);
+        self.store = 0.0;
+    }
+}
+
+/// Schroeder のオールパス（Freeverb の近似版）
+#[derive(Debug)]
+struct AllPass {
+    buf: Vec<f32>,
+    pos: usize,
+}
+
+impl AllPass {
+    #[inline]
+    fn process(&mut self, x: f32) -> f32 {
+        let b = self.buf[self.pos];
+        self.buf[self.pos] = x + b * 0.5;
+        self.pos = (self.pos + 1) % self.buf.len();
+        b - x
+    }
+}
+
+/// Freeverb 型のリバーブ（8 本のコムと 4 段のオールパスを左右に持つ）
+/// 遅延長はサンプルレートに合わせて伸縮し、バッファは size 最大のぶん確保しておく
+#[derive(Debug)]
+pub struct Reverb {
+    sr: f32,
+    params: ReverbParams,
+    combs: [Vec<Comb>; 2],
+    allpasses: [Vec<AllPass>; 2],
+    pre: Vec<f32>,
+    pre_pos: usize,
+    pre_len: usize,
+}
+
+impl Reverb {
+    pub fn new(sr: f32, params: ReverbParams) -> Self {
+        let ratio = sr / TUNING_SR;
+        let combs = [0, STEREO_SPREAD].map(|spread| {
+            COMB_TUNING
+                .iter()
+                .map(|&t| Comb::new((((t + spread) as f32 * ratio * MAX_SCALE) as usize).max(1)))
+                .collect()
+        });
+        let allpasses = [0, STEREO_SPREAD].map(|spread| {
+            ALLPASS_TUNING
+                .iter()
+                .map(|&t| AllPass {
+                    buf: vec![0.0; (((t + spread) as f32 * ratio) as usize).max(1)],
+                    pos: 0,
+                })
+                .collect()
+        });
+        let mut r = Self {
+            sr,
+            params,
+            combs,
+            allpasses,
+            pre: vec![0.0; (MAX_PRE_DELAY_MS * 0.001 * sr) as usize + 1],
+            pre_pos: 0,
+            pre_len: 0,
+        };
+        r.set_params(params);
+        r
+    }
+
+    pub fn set_params(&mut self, params: ReverbParams) {
+        self.params = params;
+        let scale = MIN_SCALE + (MAX_SCALE - MIN_SCALE) * params.size.clamp(0.0, 1.0);
+        let damp = params.damping.clamp(0.0, 1.0) * DAMP_SCALE;
+        let rt60 = params.decay.max(0.05) * self.sr;
+        for comb in self.combs.iter_mut().flatten() {
+            let cap = comb.buf.len();
+            comb.len = ((cap as f32 * scale / MAX_SCALE) as usize).clamp(1, cap);
+            // len サンプルごとに掛かるゲインから、rt60 で -60dB になる係数を決める
+            comb.feedback = 10f32.powf(-3.0 * comb.len as f32 / rt60).min(0.998);
+            comb.damp = damp;
+        }
+        let pre = (params.pre_delay.max(0.0) * 0.001 * self.sr) as usize;
+        self.pre_len = pre.min(self.pre.len() - 1);
+    }
+}
+
+impl EffectTrait for Reverb {
+    fn process(&mut self, [l, r]: [f32; 2]) -> [f32; 2] {
+        let cap = self.pre.len();
+        self.pre[self.pre_pos] = (l + r) * INPUT_GAIN;
+        let input = self.pre[(self.pre_pos + cap - self.pre_len) % cap];
+        self.pre_pos = (self.pre_pos + 1) % cap;
+
+        let [out_l, out_r] = [0, 1].map(|ch| {
+            let mut y: f32 = self.combs[ch].iter_mut().map(|c| c.process(input)).sum();
+            for ap in self.allpasses[ch].iter_mut() {
+                y = ap.process(y);
+            }
+            y
+        });
+        let width = self.params.width.clamp(0.0, 1.0);
+        let wet1 = 0.5 + 0.5 * width;
+        let wet2 = 0.5 - 0.5 * width;
+        [out_l * wet1 + out_r * wet2, out_r * wet1 + out_l * wet2]
+    }
+
+    fn reset(&mut self) {
+        for comb in self.combs.iter_mut().flatten() {
+            comb.reset();
+        }
+        for ap in self.allpasses.iter_mut().flatten() {
+            ap.buf.fill(0.0);
+        }
+        self.pre.fill(0.0);
+    }
+}
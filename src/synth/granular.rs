@@ -0,0 +1,125 @@
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+use crate::synth::{note::midi_to_hz, rng::Rng, sample::Sample};
+
+const MAX_GRAINS: usize = 32; // ボイスあたりの同時グレイン数（事前確保）
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrainParams {
+    pub root: u8,      // サンプルの元の音程（MIDI ノート番号）
+    pub position: f32, // 0.0〜1.0: 読み出し位置（サンプル長に対する割合）
+    pub spray: f32,    // 0.0〜1.0: 読み出し位置のランダムなばらつき
+    pub size_ms: f32,  // グレインの長さ
+    pub density: f32,  // 1秒あたりのグレイン数
+    pub pitch: f32,    // 半音単位のピッチオフセット
+    pub scatter: f32,  // 0.0〜1.0: グレインごとのランダムな定位の幅
+}
+
+impl Default for GrainParams {
+    fn default() -> Self {
+        Self {
+            root: 60,
+            position: 0.5,
+            spray: 0.05,
+            size_ms: 80.0,
+            density: 40.0,
+            pitch: 0.0,
+            scatter: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Grain {
+    active: bool,
+    pos: f64,
+    inc: f64,
+    age: u32,
+    len: u32,
+    gain_l: f32,
+    gain_r: f32,
+}
+
+/// ノートごとのグレインの集まり
+/// グレインは固定長の配列から割り当てるのでオーディオスレッドで確保しない
+#[derive(Debug, Clone, Copy)]
+pub struct GrainCloud {
+    grains: [Grain; MAX_GRAINS],
+    freq: f32,
+    until_next: f32, // 次のグレインまでのサンプル数
+}
+
+impl Default for GrainCloud {
+    fn default() -> Self {
+        Self {
+            grains: [Grain::default(); MAX_GRAINS],
+            freq: 0.0,
+            until_next: 0.0,
+        }
+    }
+}
+
+impl GrainCloud {
+    pub fn new(freq_hz: f32) -> Self {
+        Self {
+            freq: freq_hz,
+            ..Default::default()
+        }
+    }
+
+    fn spawn(&mut self, sample: &Sample, params: &GrainParams, sr: f32, rng: &mut Rng) {
+        let Some(g) = self.grains.iter_mut().find(|g| !g.active) else {
+            return;
+        };
+        let len = sample.data.len() as f32;
+        let start = (params.position + params.spray * rng.next_bipolar() * 0.5).clamp(0.0, 1.0);
+        let ratio = self.freq / midi_to_hz(params.root as f32)
+            * 2.0f32.powf(params.pitch / 12.0)
+            * (sample.sr / sr);
+        // 等パワーパン
+        let pan = (params.scatter.clamp(0.0, 1.0) * rng.next_bipolar() + 1.0) * FRAC_PI_4;
+        *g = Grain {
+            active: true,
+            pos: (start * len) as f64,
+            inc: ratio as f64,
+            age: 0,
+            len: (params.size_ms.max(1.0) * 0.001 * sr) as u32,
+            gain_l: pan.cos(),
+            gain_r: pan.sin(),
+        };
+    }
+
+    /// 1サンプル進めて (L, R) を返す
+    pub fn next_frame(
+        &mut self,
+        sample: &Sample,
+        params: &GrainParams,
+        sr: f32,
+        rng: &mut Rng,
+    ) -> (f32, f32) {
+        self.until_next -= 1.0;
+        if self.until_next <= 0.0 {
+            self.spawn(sample, params, sr, rng);
+            self.until_next += sr / params.density.max(0.1);
+        }
+
+        let (mut l, mut r) = (0.0, 0.0);
+        for g in self.grains.iter_mut().filter(|g| g.active) {
+            // Hann 窓
+            let w = 0.5 - 0.5 * (TAU * g.age as f32 / g.len as f32).cos();
+            let s = sample.hermite(g.pos) * w;
+            l += s * g.gain_l;
+            r += s * g.gain_r;
+            g.pos += g.inc;
+            g.age += 1;
+            if g.age >= g.len {
+                g.active = false;
+            }
+        }
+
+        // 重なり数に応じて音量をそろえる
+        let overlap = (params.density * params.size_ms * 0.001).max(1.0);
+        let norm = overlap.sqrt().recip();
+        (l * norm, r * norm)
+    }
+}
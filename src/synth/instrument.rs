@@ -0,0 +1,361 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use crate::synth::{
+    note::parse_note_name,
+    sample::{LoopMode, Sample, SampleParams},
+};
+
+/// キー・ベロシティ範囲にサンプルを割り当てたゾーン
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region {
+    pub sample: usize, // Instrument::samples のインデックス
+    pub lokey: u8,
+    pub hikey: u8,
+    pub lovel: u8,
+    pub hivel: u8,
+    pub params: SampleParams,
+    pub ampeg: Option<[f32; 4]>, // (attack, decay, sustain, release)。None なら Synth の ADSR
+    pub seq_length: u8,          // ラウンドロビンの周期
+    pub seq_position: u8,        // 1 始まり
+    pub group: usize,            // 属する <group> の番号（ラウンドロビンのカウンタを共有する）
+}
+
+impl Region {
+    /// key/vel が範囲内かどうか
+    pub fn matches(&self, key: u8, vel: u8) -> bool {
+        (self.lokey..=self.hikey).contains(&key) && (self.lovel..=self.hivel).contains(&vel)
+    }
+
+    /// ラウンドロビンのカウンタ rr で鳴らす番かどうか
+    pub fn in_turn(&self, rr: u32) -> bool {
+        let seq = self.seq_length.max(1) as u32;
+        rr % seq + 1 == self.seq_position.clamp(1, self.seq_length.max(1)) as u32
+    }
+}
+
+/// 複数サンプルをキー/ベロシティで割り当てた楽器
+#[derive(Debug, Default)]
+pub struct Instrument {
+    pub samples: Vec<Sample>,
+    pub regions: Vec<Region>,
+    round_robin: Vec<AtomicU32>, // <group> ごとのラウンドロビンカウンタ（オーディオスレッドだけが進める）
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SfzError {
+    Sample { path: String, reason: String },
+    BadValue { opcode: String, value: String },
+    NoRegions,
+}
+
+impl fmt::Display for SfzError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SfzError::Sample { path, reason } => write!(f, "{path}: {reason}"),
+            SfzError::BadValue { opcode, value } => {
+                write!(f, "invalid value '{value}' for {opcode}")
+            }
+            SfzError::NoRegions => write!(f, "no <region> found"),
+        }
+    }
+}
+
+impl std::error::Error for SfzError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Header {
+    Control,
+    Global,
+    Group,
+    Region,
+    Other,
+}
+
+type Opcodes = HashMap<String, String>;
+
+impl Instrument {
+    /// ゾーンの属する <group> のラウンドロビンカウンタ
+    pub fn round_robin(&self, region: &Region) -> u32 {
+        self.round_robin
+            .get(region.group)
+            .map_or(0, |c| c.load(Ordering::Relaxed))
+    }
+
+    /// key/vel に合うゾーンを含む <group> のカウンタを1つずつ進める
+    pub fn advance_round_robin(&self, key: u8, vel: u8) {
+        // ゾーンは <group> の順に並んでいるので、直前と同じ番号なら進め済み
+        let mut last = None;
+        for r in self.regions.iter().filter(|r| r.matches(key, vel)) {
+            if last != Some(r.group) {
+                if let Some(c) = self.round_robin.get(r.group) {
+                    c.fetch_add(1, Ordering::Relaxed);
+                }
+                last = Some(r.group);
+            }
+        }
+    }
+
+    /// SFZ のサブセットを読み込む
+    ///
+    /// 対応: `<control>` の `default_path`、`<global>`/`<group>`/`<region>` の継承、
+    /// `sample`, `key`, `lokey`/`hikey`, `pitch_keycenter`, `lovel`/`hivel`,
+    /// `loop_mode`, `loop_start`/`loop_end`, `loop_crossfade`, `ampeg_*`,
+    /// `seq_length`/`seq_position`（カウンタは `<group>` ごと）。
+    /// `load` はサンプルのパスを受け取り、デコード済みのサンプルを返す。
+    pub fn from_sfz(
+        text: &str,
+        mut load: impl FnMut(&str) -> Result<Sample, String>,
+    ) -> Result<Self, SfzError> {
+        let mut inst = Instrument::default();
+        let mut loaded: HashMap<String, usize> = HashMap::new();
+        let mut default_path = String::new();
+        let (mut global, mut group, mut region) = (Opcodes::new(), Opcodes::new(), Opcodes::new());
+        let mut header = Header::Other;
+        let mut group_idx = 0;
+
+        let mut finish = |header: Header,
+                          region: &Opcodes,
+                          group: &Opcodes,
+                          global: &Opcodes,
+                          default_path: &str,
+                          group_idx: usize,
+                          inst: &mut Instrument|
+         -> Result<(), SfzError> {
+            if header != Header::Region {
+                return Ok(());
+            }
+            // region > group > global の順で優先
+            let get = |k: &str| {
+                region
+                    .get(k)
+                    .or_else(|| group.get(k))
+                    .or_else(|| global.get(k))
+                    .map(String::as_str)
+            };
+            let Some(path) = get("sample") else {
+                return Ok(());
+            };
+            let path = format!("{default_path}{path}").replace('\\', "/");
+            let sample = match loaded.get(&path) {
+                Some(&i) => i,
+                None => {
+                    let s = load(&path).map_err(|reason| SfzError::Sample {
+                        path: path.clone(),
+                        reason,
+                    })?;
+                    inst.samples.push(s);
+                    loaded.insert(path, inst.samples.len() - 1);
+                    inst.samples.len() - 1
+                }
+            };
+            let sr = inst.samples[sample].sr;
+            inst.regions.push(Region {
+                group: group_idx,
+                ..parse_region(sample, sr, get)?
+            });
+            Ok(())
+        };
+
+        for line in text.lines() {
+            let line = line.split("//").next().unwrap_or("");
+            for token in tokenize(line) {
+                match token {
+                    Token::Header(name) => {
+                        finish(
+                            header,
+                            &region,
+                            &group,
+                            &global,
+                            &default_path,
+                            group_idx,
+                            &mut inst,
+                        )?;
+                        region.clear();
+                        header = match name {
+                            "control" => Header::Control,
+                            "global" => {
+                                global.clear();
+                                group.clear();
+                                group_idx += 1;
+                                Header::Global
+                            }
+                            "group" => {
+                                group.clear();
+                                group_idx += 1;
+                                Header::Group
+                            }
+                            "region" => Header::Region,
+                            _ => Header::Other,
+                        };
+                    }
+                    Token::Opcode(k, v) => {
+                        let target = match header {
+                            Header::Control => {
+                                if k == "default_path" {
+                                    default_path = v.replace('\\', "/");
+                                }
+                                continue;
+                            }
+                            Header::Global => &mut global,
+                            Header::Group => &mut group,
+                            Header::Region => &mut region,
+                            Header::Other => continue,
+                        };
+                        target.insert(k.to_owned(), v.to_owned());
+                    }
+                }
+            }
+        }
+        finish(
+            header,
+            &region,
+            &group,
+            &global,
+            &default_path,
+            group_idx,
+            &mut inst,
+        )?;
+
+        if inst.regions.is_empty() {
+            return Err(SfzError::NoRegions);
+        }
+        inst.round_robin = (0..=group_idx).map(|_| AtomicU32::new(0)).collect();
+        Ok(inst)
+    }
+}
+
+enum Token<'a> {
+    Header(&'a str),
+    Opcode(&'a str, &'a str),
+}
+
+/// 1行をヘッダと opcode に分ける
+/// 値は空白を含みうる（sample=My Piano C4.wav）ので、次の `key=` かヘッダの手前までを値とする
+fn tokenize(line: &str) -> Vec<Token<'_>> {
+    let mut out = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('<') {
+            let end = r.find('>').unwrap_or(r.len());
+            out.push(Token::Header(r[..end].trim()));
+            rest = r.get(end + 1..).unwrap_or("").trim_start();
+            continue;
+        }
+        let Some(eq) = rest.find('=') else {
+            break;
+        };
+        let key = rest[..eq].trim();
+        let after = &rest[eq + 1..];
+        let end = next_token_start(after);
+        out.push(Token::Opcode(key, after[..end].trim()));
+        rest = after[end..].trim_start();
+    }
+    out
+}
+
+/// 次のヘッダか ` key=` が始まる位置
+fn next_token_start(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'<' {
+            return i;
+        }
+        if bytes[i].is_ascii_whitespace() {
+            let word_start = i + 1;
+            let mut j = word_start;
+            while j < bytes.len() && (bytes[j].is_ascii_alphanumeric() || bytes[j] == b'_') {
+                j += 1;
+            }
+            if j > word_start && j < bytes.len() && bytes[j] == b'=' {
+                return i;
+            }
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+fn parse_region<'a>(
+    sample: usize,
+    sr: f32,
+    get: impl Fn(&str) -> Option<&'a str>,
+) -> Result<Region, SfzError> {
+    let bad = |opcode: &str, value: &str| SfzError::BadValue {
+        opcode: opcode.to_owned(),
+        value: value.to_owned(),
+    };
+    let key_of = |k: &str| -> Result<Option<u8>, SfzError> {
+        get(k)
+            .map(|v| parse_note_name(v).ok_or_else(|| bad(k, v)))
+            .transpose()
+    };
+    let num_of = |k: &str| -> Result<Option<f32>, SfzError> {
+        get(k)
+            .map(|v| v.parse::<f32>().map_err(|_| bad(k, v)))
+            .transpose()
+    };
+
+    // key= は lokey/hikey/pitch_keycenter をまとめて設定する
+    let key = key_of("key")?;
+    let lokey = key_of("lokey")?.or(key).unwrap_or(0);
+    let hikey = key_of("hikey")?.or(key).unwrap_or(127);
+    let root = key_of("pitch_keycenter")?.or(key).unwrap_or(60);
+    let lovel = num_of("lovel")?.unwrap_or(1.0) as u8;
+    let hivel = num_of("hivel")?.unwrap_or(127.0) as u8;
+
+    let loop_start = num_of("loop_start")?.or(num_of("loopstart")?);
+    let loop_end = num_of("loop_end")?.or(num_of("loopend")?);
+    let mode = match get("loop_mode").or(get("loopmode")) {
+        Some("loop_continuous") => LoopMode::Loop,
+        Some("loop_sustain") => LoopMode::Sustain,
+        Some("one_shot") => LoopMode::OneShot,
+        Some("no_loop") => LoopMode::NoLoop,
+        Some(v) => return Err(bad("loop_mode", v)),
+        // loop_mode 未指定でもループ点があればループ
+        None if loop_end.is_some() => LoopMode::Loop,
+        None => LoopMode::NoLoop,
+    };
+    let crossfade = num_of("loop_crossfade")?.unwrap_or(0.0);
+
+    let ampeg = [
+        "ampeg_attack",
+        "ampeg_decay",
+        "ampeg_sustain",
+        "ampeg_release",
+    ];
+    let ampeg = if ampeg.iter().any(|k| get(k).is_some()) {
+        Some([
+            num_of(ampeg[0])?.unwrap_or(0.0),
+            num_of(ampeg[1])?.unwrap_or(0.0),
+            num_of(ampeg[2])?.unwrap_or(100.0) / 100.0,
+            num_of(ampeg[3])?.unwrap_or(0.001),
+        ])
+    } else {
+        None
+    };
+
+    Ok(Region {
+        sample,
+        lokey,
+        hikey,
+        lovel,
+        hivel,
+        params: SampleParams {
+            root,
+            mode,
+            loop_start: loop_start.unwrap_or(0.0) as usize,
+            // SFZ の loop_end は最後のフレームを含む
+            loop_end: loop_end.map(|e| e as usize + 1).unwrap_or(0),
+            crossfade: (crossfade * sr) as usize,
+        },
+        ampeg,
+        seq_length: num_of("seq_length")?.unwrap_or(1.0) as u8,
+        seq_position: num_of("seq_position")?.unwrap_or(1.0) as u8,
+        group: 0,
+    })
+}
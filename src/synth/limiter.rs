@@ -0,0 +1,186 @@
+const MAX_LOOKAHEAD: usize = 1024; // 192kHz でも 5ms 入る（確保し直さないよう固定長）
+const MAX_KNEE: f32 = 0.891; // -1dB。ceiling が 0dB 近くてもカーブをかける幅を残す
+
+/// 最後に掛けるクリップのカーブ
+/// どれも knee まではそのまま通し、knee から ±1.0 までの間で丸める
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ClipMode {
+    #[default]
+    Hard, // ±1.0 で切る
+    Tanh,
+    Cubic, // u - 4u³/27（u = 1.5 で頭打ち）
+}
+
+impl ClipMode {
+    #[inline]
+    pub fn apply(self, x: f32, knee: f32) -> f32 {
+        let a = x.abs();
+        if a <= knee {
+            return x;
+        }
+        // knee を超えた分を残りの幅 w で正規化してカーブに通す（knee で傾き 1 につながる）
+        let w = 1.0 - knee;
+        let u = (a - knee) / w;
+        let y = match self {
+            ClipMode::Hard => return x.clamp(-1.0, 1.0),
+            ClipMode::Tanh => u.tanh(),
+            ClipMode::Cubic => {
+                let u = u.min(1.5);
+                u - (4.0 / 27.0) * u * u * u
+            }
+        };
+        (knee + w * y).copysign(x)
+    }
+}
+
+/// マスターのリミッター設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimiterParams {
+    pub enabled: bool,
+    pub ceiling_db: f32,   // これを超えないようにゲインを下げる
+    pub lookahead_ms: f32, // 先読み時間（そのぶん出力が遅れる）
+    pub release_ms: f32,   // ゲインが戻るまでの時間
+    pub clip: ClipMode,
+}
+
+impl Default for LimiterParams {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ceiling_db: -0.3,
+            lookahead_ms: 2.0,
+            release_ms: 100.0,
+            clip: ClipMode::Hard,
+        }
+    }
+}
+
+/// 先読み付きのブリックウォール・リミッター
+///
+/// 必要ゲインを先読み区間で最小値ホールドし、同じ長さの移動平均で滑らかにする。
+/// 平均をとる区間のどこでもピークの必要ゲイン以下になっているので、
+/// 遅延させた信号がピークに着くときには必ず ceiling 以下まで下がっている。
+#[derive(Clone)]
+pub struct Limiter {
+    params: LimiterParams,
+    ceiling: f32,
+    knee: f32,  // クリップのカーブをかけ始める振幅
+    len: usize, // 先読みのサンプル数
+    release: f32,
+    delay: [[f32; 2]; MAX_LOOKAHEAD],
+    hold: [f32; MAX_LOOKAHEAD], // 移動平均用に残しておく最小値ホールド後のゲイン
+    sum: f64,
+    // 区間の最小値を求める単調キュー（添字と値）
+    min_pos: [u64; MAX_LOOKAHEAD],
+    min_val: [f32; MAX_LOOKAHEAD],
+    min_head: usize,
+    min_len: usize,
+    released: f32, // リリースをかけたゲイン
+    pos: usize,
+    frames: u64,
+    gain: f32,     // 直近に掛けたゲイン
+    min_gain: f32, // take_min_gain までの最小ゲイン（GUI 表示用）
+}
+
+impl Limiter {
+    pub fn new(sr: f32, params: LimiterParams) -> Self {
+        let mut l = Self {
+            params,
+            ceiling: 1.0,
+            knee: MAX_KNEE,
+            len: 1,
+            release: 0.0,
+            delay: [[0.0; 2]; MAX_LOOKAHEAD],
+            hold: [1.0; MAX_LOOKAHEAD],
+            sum: 0.0,
+            min_pos: [0; MAX_LOOKAHEAD],
+            min_val: [1.0; MAX_LOOKAHEAD],
+            min_head: 0,
+            min_len: 0,
+            released: 1.0,
+            pos: 0,
+            frames: 0,
+            gain: 1.0,
+            min_gain: 1.0,
+        };
+        l.set_params(sr, params);
+        l
+    }
+
+    pub fn set_params(&mut self, sr: f32, params: LimiterParams) {
+        self.ceiling = 10f32.powf(params.ceiling_db.min(0.0) / 20.0);
+        self.knee = self.ceiling.min(MAX_KNEE);
+        self.release = (-1.0 / (params.release_ms.max(1.0) * 0.001 * sr)).exp();
+        let len = ((params.lookahead_ms * 0.001 * sr) as usize).clamp(1, MAX_LOOKAHEAD - 1);
+        // 先読みの長さが変わったらバッファを空にする
+        if len != self.len || params.enabled != self.params.enabled {
+            self.len = len;
+            self.reset();
+        }
+        self.params = params;
+    }
+
+    pub fn reset(&mut self) {
+        self.delay[..self.len].fill([0.0; 2]);
+        self.hold[..self.len].fill(1.0);
+        self.sum = self.len as f64;
+        self.min_head = 0;
+        self.min_len = 0;
+        self.released = 1.0;
+        self.pos = 0;
+    }
+
+    /// 前回呼んでからの最小ゲイン（1.0 ならリミットしていない）
+    pub fn take_min_gain(&mut self) -> f32 {
+        std::mem::replace(&mut self.min_gain, self.gain)
+    }
+
+    #[inline]
+    pub fn process(&mut self, frame: [f32; 2]) -> [f32; 2] {
+        let (clip, knee) = (self.params.clip, self.knee);
+        if !self.params.enabled {
+            self.gain = 1.0;
+            return frame.map(|x| clip.apply(x, knee));
+        }
+        let peak = frame[0].abs().max(frame[1].abs());
+        let need = if peak > self.ceiling {
+            self.ceiling / peak
+        } else {
+            1.0
+        };
+
+        // 先読み区間の最小値（遅延 len に対して len + 1 サンプル分見る）
+        let n = self.frames;
+        self.frames += 1;
+        while self.min_len > 0 {
+            let back = (self.min_head + self.min_len - 1) % MAX_LOOKAHEAD;
+            if self.min_val[back] < need {
+                break;
+            }
+            self.min_len -= 1;
+        }
+        let back = (self.min_head + self.min_len) % MAX_LOOKAHEAD;
+        self.min_pos[back] = n;
+        self.min_val[back] = need;
+        self.min_len += 1;
+        if self.min_pos[self.min_head] + (self.len as u64) < n {
+            self.min_head = (self.min_head + 1) % MAX_LOOKAHEAD;
+            self.min_len -= 1;
+        }
+        let held = self.min_val[self.min_head];
+
+        // 下げるときは即座に、戻すときはリリースで
+        self.released = held.min(held + (self.released - held) * self.release);
+
+        // 移動平均
+        self.sum += (self.released - self.hold[self.pos]) as f64;
+        self.hold[self.pos] = self.released;
+        let gain = ((self.sum / self.len as f64) as f32).min(1.0);
+
+        let delayed = std::mem::replace(&mut self.delay[self.pos], frame);
+        self.pos = (self.pos + 1) % self.len;
+        self.gain = gain;
+        self.min_gain = self.min_gain.min(gain);
+        delayed.map(|x| clip.apply(x * gain, knee))
+    }
+}
@@ -40,6 +40,17 @@ pub enum Note {
     B5,
 }
 
+impl Note {
+    /// MIDI ノート番号（C3 = 48）
+    pub fn midi(self) -> Option<u8> {
+        match self {
+            Note::None => None,
+            // 列挙の並びは C3 から半音ずつ
+            n => Some(n as u8 + 47),
+        }
+    }
+}
+
 impl From<Note> for f32 {
     fn from(note: Note) -> Self {
         match note {
@@ -83,3 +94,56 @@ impl From<Note> for f32 {
         }
     }
 }
+
+/// MIDI ノート番号（小数可）から周波数へ（A4 = 69 = 440Hz）
+pub fn midi_to_hz(note: f32) -> f32 {
+    440.0 * 2.0f32.powf((note - 69.0) / 12.0)
+}
+
+/// MIDI ノート番号の音名（例: 60 → "C4"）
+pub fn midi_name(note: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+    ];
+    format!("{}{}", NAMES[note as usize % 12], note as i32 / 12 - 1)
+}
+
+/// 周波数から MIDI ノート番号（小数）へ
+pub fn hz_to_midi(hz: f32) -> f32 {
+    69.0 + 12.0 * (hz.max(1e-3) / 440.0).log2()
+}
+
+/// 周波数を音名とセントで表す（例: 450Hz → "A4 +39c"）
+pub fn hz_name(hz: f32) -> String {
+    let midi = hz_to_midi(hz).clamp(0.0, 127.0);
+    let nearest = midi.round();
+    let cents = ((midi - nearest) * 100.0).round() as i32;
+    format!("{} {cents:+}c", midi_name(nearest as u8))
+}
+
+/// MIDI ノート番号または音名（c4, c#4, db4。C4 = 60）を読む
+pub fn parse_note_name(v: &str) -> Option<u8> {
+    if let Ok(n) = v.trim().parse::<i32>() {
+        return u8::try_from(n).ok().filter(|n| *n <= 127);
+    }
+    let v = v.trim().to_ascii_lowercase();
+    let mut chars = v.chars();
+    let base = match chars.next()? {
+        'c' => 0,
+        'd' => 2,
+        'e' => 4,
+        'f' => 5,
+        'g' => 7,
+        'a' => 9,
+        'b' => 11,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    let (acc, octave) = match rest.as_bytes().first()? {
+        b'#' => (1, &rest[1..]),
+        b'b' if rest.len() > 1 => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let n = (octave.parse::<i32>().ok()? + 1) * 12 + base + acc;
+    u8::try_from(n).ok().filter(|n| *n <= 127)
+}
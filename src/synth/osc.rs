@@ -1,5 +1,7 @@
 use std::f32::consts::TAU;
 
+use crate::synth::additive::Wavetable;
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Osc {
     phase: f32,
@@ -21,13 +23,35 @@ impl Osc {
         }
     }
 
+    /// 開始位相を指定する（0.0〜1.0 で1周期）
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase.rem_euclid(1.0) * TAU;
+        self
+    }
+
+    pub fn set_freq(&mut self, freq_hz: f32, sr: f32) {
+        self.phase_inc = (freq_hz / sr) * TAU;
+    }
+
     pub fn next_sample(&mut self) -> f32 {
         let sample = self.waveform.sample(self.phase, self.phase_inc);
+        self.advance();
+        sample * self.amp
+    }
+
+    /// `Waveform::Additive` 用: ウェーブテーブルを読んで1サンプル進める
+    pub fn next_sample_table(&mut self, table: &Wavetable) -> f32 {
+        let sample = table.sample(self.phase, self.phase_inc);
+        self.advance();
+        sample * self.amp
+    }
+
+    #[inline]
+    fn advance(&mut self) {
         self.phase += self.phase_inc;
         if self.phase >= TAU {
             self.phase -= TAU;
         }
-        sample * self.amp
     }
 
     pub fn set_waveform(&mut self, waveform: Waveform) {
@@ -46,6 +70,8 @@ pub enum Waveform {
     Triangle {
         curve: f32,
     },
+    // 倍音スペクトルから作ったウェーブテーブル（テーブル本体は Synth が持つ）
+    Additive,
 }
 
 impl Waveform {
@@ -72,6 +98,55 @@ impl Waveform {
                 let tri = 1.0 - 4.0 * (t - 0.5).abs();
                 tri.signum() * tri.abs().powf(1.0 + *curve * 2.0)
             }
+            // テーブル未受信時はサイン波で代用
+            Waveform::Additive => phase.sin(),
+        }
+    }
+}
+
+/// ノートオン時のオシレータ開始位相の決め方
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PhaseMode {
+    #[default]
+    Reset, // 毎回 0 から
+    Random,  // ランダムな位相から
+    FreeRun, // ノートと無関係に回り続けている位相から
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SubShape {
+    #[default]
+    Square,
+    Sine,
+}
+
+/// メインオシレータの 1〜2 オクターブ下を鳴らすサブオシレータ
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubOsc {
+    pub shape: SubShape,
+    pub octave: u8, // 何オクターブ下げるか（1 or 2）
+    pub level: f32,
+}
+
+impl Default for SubOsc {
+    fn default() -> Self {
+        Self {
+            shape: SubShape::Square,
+            octave: 1,
+            level: 0.5,
+        }
+    }
+}
+
+impl SubOsc {
+    pub fn freq(&self, base_hz: f32) -> f32 {
+        base_hz / (1u32 << self.octave.clamp(1, 2)) as f32
+    }
+
+    pub fn waveform(&self) -> Waveform {
+        match self.shape {
+            SubShape::Square => Waveform::Square { pulse_width: 0.5 },
+            SubShape::Sine => Waveform::Sine,
         }
     }
 }
@@ -0,0 +1,129 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::synth::rng::Rng;
+
+/// 遅延線の長さを決める最低周波数（MIDI ノート 0 ≒ 8.18Hz まで音程どおりに鳴らせる）
+/// これより低い周波数はこの値に丸める
+const MIN_FREQ: f32 = 8.0;
+const MIN_FRAC_DELAY: f32 = 0.1; // オールパスの遅延を 0.1〜1.1 サンプルに収める
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PluckParams {
+    pub damping: f32,    // 0.0〜1.0: 高域の減衰の速さ
+    pub brightness: f32, // 0.0〜1.0: 励振ノイズの明るさ
+    pub decay: f32,      // 秒: -60dB までの減衰時間
+}
+
+impl Default for PluckParams {
+    fn default() -> Self {
+        Self {
+            damping: 0.5,
+            brightness: 0.7,
+            decay: 2.0,
+        }
+    }
+}
+
+/// Karplus-Strong 方式の撥弦モデル
+/// 遅延線 + ループフィルタ（1次 FIR ローパス）+ 小数遅延用の1次オールパス
+#[derive(Debug, Clone, Default)]
+pub struct Pluck {
+    buf: Vec<f32>, // 生成時に確保した遅延線（オーディオスレッドでは再確保しない）
+    len: usize,    // 現在の整数遅延長
+    pos: usize,
+    freq: f32,
+    lp_s: f32, // ループフィルタ係数 (0.0〜0.5)
+    lp_x1: f32,
+    ap_c: f32, // オールパス係数
+    ap_x1: f32,
+    ap_y1: f32,
+    gain: f32, // 1周期あたりのループゲイン
+}
+
+impl Pluck {
+    pub fn new(sr: f32) -> Self {
+        Self {
+            buf: vec![0.0; (sr / MIN_FREQ).ceil() as usize + 2],
+            ..Default::default()
+        }
+    }
+
+    /// 遅延線をノイズバーストで満たして弦を弾く
+    pub fn excite(&mut self, freq_hz: f32, sr: f32, params: PluckParams, rng: &mut Rng) {
+        self.freq = freq_hz.max(MIN_FREQ);
+        self.set_params(sr, params);
+        if self.len == 0 {
+            return;
+        }
+
+        // 明るさに応じてノイズを1極ローパスに通す
+        let a = 0.05 + 0.95 * params.brightness.clamp(0.0, 1.0).powi(2);
+        let mut y = 0.0;
+        let mut sum = 0.0;
+        for s in self.buf[..self.len].iter_mut() {
+            y += a * (rng.next_bipolar() - y);
+            *s = y;
+            sum += y;
+        }
+        // DC を取り除く
+        let mean = sum / self.len as f32;
+        self.buf[..self.len].iter_mut().for_each(|s| *s -= mean);
+
+        self.pos = 0;
+        self.lp_x1 = 0.0;
+        self.ap_x1 = 0.0;
+        self.ap_y1 = 0.0;
+    }
+
+    /// 減衰・ダンピングを変更し、ループ全体の遅延が1周期になるよう調律し直す
+    pub fn set_params(&mut self, sr: f32, params: PluckParams) {
+        if self.freq <= 0.0 || self.buf.is_empty() {
+            return;
+        }
+        self.lp_s = params.damping.clamp(0.0, 1.0) * 0.5;
+
+        // 1周期 = 整数遅延 + ループフィルタの遅延 + オールパスの小数遅延
+        // ループフィルタとオールパスの遅延は周波数で変わるので、基本波での位相遅延で合わせる
+        // （低域近似の lp_s, (1 - c) / (1 + c) のままだと高い音ほどずれる）
+        let period = sr / self.freq;
+        let w = (TAU * self.freq / sr).min(0.99 * PI);
+        let lp_delay = (self.lp_s * w.sin()).atan2(1.0 - self.lp_s + self.lp_s * w.cos()) / w;
+        let max_len = self.buf.len() - 1;
+        let len = ((period - lp_delay - MIN_FRAC_DELAY).floor() as usize).clamp(1, max_len);
+        let frac = (period - lp_delay - len as f32).max(0.0);
+        self.ap_c = (0.5 * w * (1.0 - frac)).sin() / (0.5 * w * (1.0 + frac)).sin();
+        if len != self.len {
+            self.len = len;
+            self.pos %= len;
+        }
+
+        // 1周期ごとに decay 秒で -60dB となるゲイン
+        let cycles = params.decay.max(1e-3) * self.freq;
+        self.gain = 0.001f32.powf(1.0 / cycles);
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        let out = self.buf[self.pos];
+
+        let lp = (1.0 - self.lp_s) * out + self.lp_s * self.lp_x1;
+        self.lp_x1 = out;
+        let ap = self.ap_c * lp + self.ap_x1 - self.ap_c * self.ap_y1;
+        self.ap_x1 = lp;
+        self.ap_y1 = ap;
+
+        let mut fb = ap * self.gain;
+        // デノーマル対策
+        if fb.abs() < 1.0e-12 {
+            fb = 0.0;
+        }
+        self.buf[self.pos] = fb;
+        self.pos += 1;
+        if self.pos >= self.len {
+            self.pos = 0;
+        }
+        out
+    }
+}
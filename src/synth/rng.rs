@@ -0,0 +1,42 @@
+/// オーディオスレッドで使える軽量な擬似乱数 (xorshift32)
+#[derive(Debug, Clone, Copy)]
+pub struct Rng {
+    state: u32,
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(0x1234_5678)
+    }
+}
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        // 0 だと永久に 0 を返すので避ける
+        Self {
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+        }
+    }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// [0, 1) の一様乱数
+    #[inline]
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// [-1, 1) の一様乱数
+    #[inline]
+    pub fn next_bipolar(&mut self) -> f32 {
+        self.next_f32() * 2.0 - 1.0
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+
+/// 読み込み済みのモノラルサンプル
+#[derive(Debug, Clone, Default)]
+pub struct Sample {
+    pub data: Vec<f32>,
+    pub sr: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WavError {
+    NotRiff,
+    MissingChunk(&'static str),
+    Unsupported { format: u16, bits: u16 },
+    InvalidFmt(&'static str), // fmt チャンクの値がありえない（どの値か）
+    Truncated,
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WavError::NotRiff => write!(f, "not a RIFF/WAVE file"),
+            WavError::MissingChunk(id) => write!(f, "missing '{id}' chunk"),
+            WavError::Unsupported { format, bits } => {
+                write!(f, "unsupported format {format} ({bits} bit)")
+            }
+            WavError::InvalidFmt(field) => write!(f, "invalid {field} in 'fmt ' chunk"),
+            WavError::Truncated => write!(f, "file is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for WavError {}
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+impl Sample {
+    /// WAV（PCM 8/16/24/32bit, float 32/64bit）を読み込み、モノラルにミックスする
+    pub fn from_wav(bytes: &[u8]) -> Result<Self, WavError> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(WavError::NotRiff);
+        }
+
+        let mut fmt: Option<(u16, usize, u32, u16)> = None; // (format, channels, sr, bits)
+        let mut data: Option<&[u8]> = None;
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id = &bytes[pos..pos + 4];
+            let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            // サイズはファイルから読んだ値なので、32 ビット環境でも溢れないよう確かめる
+            let end = (pos + 8).checked_add(size).ok_or(WavError::Truncated)?;
+            let body = &bytes[pos + 8..end.min(bytes.len())];
+            match id {
+                b"fmt " => {
+                    if body.len() < 16 {
+                        return Err(WavError::Truncated);
+                    }
+                    let mut format = u16::from_le_bytes([body[0], body[1]]);
+                    let channels = u16::from_le_bytes([body[2], body[3]]) as usize;
+                    let sr = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                    let bits = u16::from_le_bytes([body[14], body[15]]);
+                    // WAVE_FORMAT_EXTENSIBLE はサブフォーマット GUID の先頭2バイトが実際の形式
+                    if format == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26 {
+                        format = u16::from_le_bytes([body[24], body[25]]);
+                    }
+                    fmt = Some((format, channels, sr, bits));
+                }
+                b"data" => data = Some(body),
+                _ => {}
+            }
+            // チャンクは2バイト境界に揃えられている
+            pos = end.checked_add(size & 1).ok_or(WavError::Truncated)?;
+        }
+
+        let (format, channels, sr, bits) = fmt.ok_or(WavError::MissingChunk("fmt "))?;
+        let data = data.ok_or(WavError::MissingChunk("data"))?;
+        if channels == 0 {
+            return Err(WavError::InvalidFmt("channel count"));
+        }
+        if sr == 0 {
+            return Err(WavError::InvalidFmt("sample rate"));
+        }
+        // 下の width = bits / 8 がバイト単位で割り切れる前提
+        if bits == 0 || bits % 8 != 0 {
+            return Err(WavError::Unsupported { format, bits });
+        }
+
+        let decode: fn(&[u8]) -> f32 = match (format, bits) {
+            (WAVE_FORMAT_PCM, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+            (WAVE_FORMAT_PCM, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            (WAVE_FORMAT_PCM, 24) => {
+                |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0
+            }
+            (WAVE_FORMAT_PCM, 32) => {
+                |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0
+            }
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => |b| f64::from_le_bytes(b.try_into().unwrap()) as f32,
+            _ => return Err(WavError::Unsupported { format, bits }),
+        };
+
+        let width = bits as usize / 8;
+        let frame = width * channels;
+        let data = data
+            .chunks_exact(frame)
+            .map(|f| f.chunks_exact(width).map(decode).sum::<f32>() / channels as f32)
+            .collect();
+        Ok(Self {
+            data,
+            sr: sr as f32,
+        })
+    }
+
+    #[inline]
+    fn at(&self, i: isize) -> f32 {
+        if i < 0 {
+            return 0.0;
+        }
+        self.data.get(i as usize).copied().unwrap_or(0.0)
+    }
+
+    /// 4点3次エルミート補間で小数位置を読む
+    #[inline]
+    pub fn hermite(&self, pos: f64) -> f32 {
+        let i = pos.floor() as isize;
+        let t = (pos - pos.floor()) as f32;
+        let (xm1, x0, x1, x2) = (self.at(i - 1), self.at(i), self.at(i + 1), self.at(i + 2));
+        let c1 = 0.5 * (x1 - xm1);
+        let c2 = xm1 - 2.5 * x0 + 2.0 * x1 - 0.5 * x2;
+        let c3 = 0.5 * (x2 - xm1) + 1.5 * (x0 - x1);
+        ((c3 * t + c2) * t + c1) * t + x0
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LoopMode {
+    #[default]
+    NoLoop, // 最後まで再生したら止まる（ノートオフでリリース）
+    Loop,    // loop_start〜loop_end を繰り返す
+    Sustain, // ノートオフまでループし、その後は最後まで再生する
+    OneShot, // ノートオフを無視して最後まで再生する
+}
+
+/// サンプル再生のパラメータ（位置はサンプルのフレーム単位）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleParams {
+    pub root: u8, // サンプルの元の音程（MIDI ノート番号）
+    pub mode: LoopMode,
+    pub loop_start: usize,
+    pub loop_end: usize, // 0 ならサンプルの終端
+    pub crossfade: usize,
+}
+
+impl Default for SampleParams {
+    fn default() -> Self {
+        Self {
+            root: 60,
+            mode: LoopMode::NoLoop,
+            loop_start: 0,
+            loop_end: 0,
+            crossfade: 0,
+        }
+    }
+}
+
+impl SampleParams {
+    /// 有効なループ範囲（ループしない場合は None）
+    fn loop_range(&self, len: usize, released: bool) -> Option<(usize, usize)> {
+        match self.mode {
+            LoopMode::Loop => {}
+            LoopMode::Sustain if !released => {}
+            _ => return None,
+        }
+        let end = if self.loop_end == 0 {
+            len
+        } else {
+            self.loop_end.min(len)
+        };
+        let start = self.loop_start.min(end);
+        (end > start + 1).then_some((start, end))
+    }
+}
+
+/// ボイスごとのサンプル再生位置
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SamplePlayer {
+    pos: f64,
+    inc: f64,
+    done: bool,
+    released: bool, // ノートオフ済み（Sustain のループを抜ける）
+}
+
+impl SamplePlayer {
+    pub fn new(freq_hz: f32, sr: f32, sample: &Sample, params: &SampleParams) -> Self {
+        let root_hz = super::note::midi_to_hz(params.root as f32);
+        Self {
+            pos: 0.0,
+            inc: (freq_hz / root_hz) as f64 * (sample.sr / sr) as f64,
+            done: false,
+            released: false,
+        }
+    }
+
+    /// ノートオフ
+    pub fn release(&mut self) {
+        self.released = true;
+    }
+
+    #[inline]
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    pub fn next_sample(&mut self, sample: &Sample, params: &SampleParams) -> f32 {
+        if self.done {
+            return 0.0;
+        }
+        let len = sample.data.len();
+        let mut out = sample.hermite(self.pos);
+
+        match params.loop_range(len, self.released) {
+            Some((start, end)) => {
+                // ループ終端の手前で、ループ開始点の手前の区間とクロスフェードする
+                let loop_len = (end - start) as f64;
+                let xfade = params.crossfade.min(start).min(end - start) as f64;
+                let fade_from = end as f64 - xfade;
+                if xfade > 0.0 && self.pos > fade_from {
+                    let t = ((self.pos - fade_from) / xfade) as f32;
+                    out = out * (1.0 - t) + sample.hermite(self.pos - loop_len) * t;
+                }
+                self.pos += self.inc;
+                while self.pos >= end as f64 {
+                    self.pos -= loop_len;
+                }
+            }
+            None => {
+                self.pos += self.inc;
+                if self.pos >= len as f64 {
+                    self.done = true;
+                }
+            }
+        }
+        out
+    }
+}
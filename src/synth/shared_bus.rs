@@ -1,29 +1,249 @@
-use std::sync::Arc;
+use std::sync::{
+    Arc,
+    atomic::{AtomicU32, AtomicU64, Ordering},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
 use crossbeam::queue::ArrayQueue;
 
-use crate::synth::{FilterType, Note, osc::Waveform};
+use crate::synth::{
+    FilterRouting, FilterSlot, FilterType, Note, VoiceType, VowelMod,
+    additive::Wavetable,
+    compressor::CompressorParams,
+    eq::EqParams,
+    fx::{Effect, FxType, MAX_FX},
+    instrument::Instrument,
+    limiter::LimiterParams,
+    osc::{PhaseMode, SubOsc, Waveform},
+    sample::Sample,
+    stereo::Stereo,
+    telemetry::Telemetry,
+};
 
 const QUEUE_CAP: usize = 2048;
+const RETIRED_CAP: usize = 64;
+const TELEMETRY_CAP: usize = 64;
 
 #[derive(Debug)]
 pub enum Msg {
-    NoteOn { note: Note },
+    NoteOn { note: Note, velocity: u8 },
     NoteOff { note: Note },
     SetMasterVolume(f32),
     SetAdsr { a: f32, d: f32, s: f32, r: f32 },
+    SetVoiceType(VoiceType),
     SetWaveform(Waveform),
-    SetFilter(Option<FilterType>),
+    SetWavetable(Arc<Wavetable>),
+    SetSample(Arc<Sample>),
+    SetInstrument(Arc<Instrument>),
+    SetFilter(FilterSlot, Option<FilterType>),
+    SetFilterRouting(FilterRouting),
+    SetVowelMod(VowelMod),
+    SetFilterKeyTrack { amount: f32, center: u8 },
+    SetSubOsc(Option<SubOsc>),
+    SetPhaseMode(PhaseMode),
+    SetSmoothing(f32), // 秒
+    SetStereo(Stereo),
+    SetEq(EqParams),
+    SetCompressor(CompressorParams),
+    SetLimiter(LimiterParams),
+    LoadFx(usize, Option<Box<Effect>>), // 種類を変えるとき（UI スレッドで確保して送る）
+    SetFx(usize, FxType),               // 読み込み済みのエフェクトのパラメータ
+    SetFxMix(usize, f32),
+    SetFxBypass(usize, bool),
+    SetFxOrder([u8; MAX_FX]),
+    SetTempo(f32), // BPM
+}
+
+/// 送った時刻付きのメッセージ
+#[derive(Debug)]
+pub struct Event {
+    pub time: f64, // SharedBus::now() の秒
+    pub msg: Msg,
+}
+
+/// オーディオスレッドで不要になったバッファ
+/// オーディオスレッドでメモリ解放しないよう UI スレッドへ送り返す
+#[derive(Debug)]
+pub enum Retired {
+    Wavetable(Arc<Wavetable>),
+    Sample(Arc<Sample>),
+    Instrument(Arc<Instrument>),
+    Effect(Box<Effect>),
 }
 
 #[derive(Clone, Debug)]
 pub struct SharedBus {
-    pub q: Arc<ArrayQueue<Msg>>,
+    pub q: Arc<ArrayQueue<Event>>,
+    deferred: Arc<ArrayQueue<Event>>, // 次のブロックへ回した 1 件（オーディオ側だけが触る）
+    pub retired: Arc<ArrayQueue<Retired>>,
+    telemetry: Arc<ArrayQueue<Telemetry>>, // オーディオ → GUI（ブロックごと、古いものから捨てる）
+    dropped: Arc<AtomicU32>,               // キューが一杯で捨てたメッセージの数
+    leaked: Arc<AtomicU32>,                // 回収キューが一杯で漏らしたバッファの数
+    sample_rate: Arc<AtomicU32>,           // f32 のビット列（オーディオ側が起動時に書く）
+    limiter_gain: Arc<AtomicU32>,          // f32 のビット列（GUI が読むまでの最小ゲイン）
+    compressor_gain: Arc<AtomicU32>,       // 同上
+    block_time: Arc<AtomicU64>,            // f64 のビット列（前のブロックを描き始めた時刻）
+    #[cfg(not(target_arch = "wasm32"))]
+    epoch: Instant,
 }
 
 impl Default for SharedBus {
     fn default() -> Self {
         let q = Arc::new(ArrayQueue::new(QUEUE_CAP));
-        Self { q }
+        let deferred = Arc::new(ArrayQueue::new(1));
+        let retired = Arc::new(ArrayQueue::new(RETIRED_CAP));
+        let telemetry = Arc::new(ArrayQueue::new(TELEMETRY_CAP));
+        let dropped = Arc::new(AtomicU32::new(0));
+        let leaked = Arc::new(AtomicU32::new(0));
+        let sample_rate = Arc::new(AtomicU32::new(48000.0f32.to_bits()));
+        let limiter_gain = Arc::new(AtomicU32::new(1.0f32.to_bits()));
+        let compressor_gain = Arc::new(AtomicU32::new(1.0f32.to_bits()));
+        let block_time = Arc::new(AtomicU64::new(f64::NAN.to_bits()));
+        Self {
+            q,
+            deferred,
+            retired,
+            telemetry,
+            dropped,
+            leaked,
+            sample_rate,
+            limiter_gain,
+            compressor_gain,
+            block_time,
+            #[cfg(not(target_arch = "wasm32"))]
+            epoch: Instant::now(),
+        }
+    }
+}
+
+impl SharedBus {
+    /// 今の時刻を付けてメッセージを送る（キューが一杯なら返す）
+    pub fn send(&self, msg: Msg) -> Result<(), Msg> {
+        self.send_at(self.now(), msg)
+    }
+
+    /// 時刻 time（now() の秒）に起きたこととしてメッセージを送る
+    pub fn send_at(&self, time: f64, msg: Msg) -> Result<(), Msg> {
+        self.q.push(Event { time, msg }).map_err(|e| {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            e.msg
+        })
+    }
+
+    /// 不要になったバッファを UI スレッドへ送り返す（オーディオ側）
+    pub fn retire(&self, r: Retired) {
+        if let Err(r) = self.retired.push(r) {
+            // オーディオスレッドで解放するよりは漏らす（UI が回収を止めているときだけ起きる）
+            std::mem::forget(r);
+            self.leaked.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 捨てたメッセージの数（起動から）
+    pub fn dropped(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 回収できずに漏らしたバッファの数（起動から）
+    pub fn leaked(&self) -> u32 {
+        self.leaked.load(Ordering::Relaxed)
+    }
+
+    /// ブロックの様子を GUI へ送る（オーディオ側、読まれていなければ古いものを捨てる）
+    pub fn report_telemetry(&self, t: Telemetry) {
+        let _ = self.telemetry.force_push(t);
+    }
+
+    /// 前回読んでから届いた様子をまとめたもの（GUI 側）
+    pub fn take_telemetry(&self) -> Option<Telemetry> {
+        let mut merged = self.telemetry.pop()?;
+        while let Some(t) = self.telemetry.pop() {
+            merged.merge(&t);
+        }
+        Some(merged)
+    }
+
+    /// メッセージの時刻に使う時計 [秒]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn now(&self) -> f64 {
+        self.epoch.elapsed().as_secs_f64()
+    }
+
+    /// メッセージの時刻に使う時計 [秒]（Web では UI と合成が同じスレッドなので performance.now）
+    #[cfg(target_arch = "wasm32")]
+    pub fn now(&self) -> f64 {
+        web_sys::window()
+            .and_then(|w| w.performance())
+            .map_or(0.0, |p| p.now() * 0.001)
+    }
+
+    /// 前のブロックを描き始めた時刻と今の時刻（オーディオ側、ブロックの頭で呼ぶ）
+    ///
+    /// この間に送られたメッセージを今のブロックに並べると、
+    /// ブロック1つ分の一定の遅れで送った間隔どおりに鳴る。
+    pub fn begin_block(&self) -> (f64, f64) {
+        let now = self.now();
+        let prev = f64::from_bits(self.block_time.swap(now.to_bits(), Ordering::Relaxed));
+        // 初回は前のブロックがないので今から
+        let start = if prev.is_finite() && prev < now {
+            prev
+        } else {
+            now
+        };
+        (start, now)
+    }
+
+    /// 時刻 until までに送られた次のメッセージ（オーディオ側）
+    ///
+    /// ブロックの途中で送られた until より後のものは取り出さず、次のブロックで返す。
+    pub fn next_event(&self, until: f64) -> Option<Event> {
+        let ev = self.deferred.pop().or_else(|| self.q.pop())?;
+        if ev.time > until {
+            // 取り出したばかりで deferred は空なので必ず入る
+            let _ = self.deferred.push(ev);
+            return None;
+        }
+        Some(ev)
+    }
+
+    /// 送り返されたバッファを（UI スレッドで）解放する
+    pub fn collect_retired(&self) {
+        while self.retired.pop().is_some() {}
+    }
+
+    pub fn set_sample_rate(&self, sr: f32) {
+        self.sample_rate.store(sr.to_bits(), Ordering::Relaxed);
+    }
+
+    /// オーディオ側のサンプルレート（起動前は 48kHz とみなす）
+    pub fn sample_rate(&self) -> f32 {
+        f32::from_bits(self.sample_rate.load(Ordering::Relaxed))
+    }
+
+    /// リミッターのゲインを報告する（オーディオ側、ブロックごと）
+    pub fn report_limiter_gain(&self, gain: f32) {
+        // 正の f32 はビット列の大小と値の大小が一致する
+        self.limiter_gain
+            .fetch_min(gain.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// 前回読んでからの最小ゲイン（GUI 側）
+    pub fn take_limiter_gain(&self) -> f32 {
+        f32::from_bits(self.limiter_gain.swap(1.0f32.to_bits(), Ordering::Relaxed))
+    }
+
+    /// コンプレッサーのゲインを報告する（オーディオ側、ブロックごと）
+    pub fn report_compressor_gain(&self, gain: f32) {
+        self.compressor_gain
+            .fetch_min(gain.max(0.0).to_bits(), Ordering::Relaxed);
+    }
+
+    /// 前回読んでからの最小ゲイン（GUI 側）
+    pub fn take_compressor_gain(&self) -> f32 {
+        f32::from_bits(
+            self.compressor_gain
+                .swap(1.0f32.to_bits(), Ordering::Relaxed),
+        )
     }
 }
@@ -0,0 +1,60 @@
+/// 目標値へ直線的に近づくパラメータ（スライダー操作のジッパーノイズ対策）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Smoothed {
+    current: f32,
+    target: f32,
+    step: f32,
+    remaining: u32, // 目標に着くまでのサンプル数
+}
+
+impl Smoothed {
+    pub fn new(value: f32) -> Self {
+        Self {
+            current: value,
+            target: value,
+            step: 0.0,
+            remaining: 0,
+        }
+    }
+
+    /// samples サンプルかけて target へ向かう（0 なら即座に）
+    pub fn set_target(&mut self, target: f32, samples: u32) {
+        self.target = target;
+        if samples == 0 || self.current == target {
+            self.current = target;
+            self.remaining = 0;
+        } else {
+            self.step = (target - self.current) / samples as f32;
+            self.remaining = samples;
+        }
+    }
+
+    #[inline]
+    pub fn value(&self) -> f32 {
+        self.current
+    }
+
+    #[inline]
+    pub fn is_ramping(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// 1サンプル進めて値を返す
+    #[inline]
+    pub fn next(&mut self) -> f32 {
+        self.advance(1)
+    }
+
+    /// n サンプル分まとめて進める（サブブロック単位の更新用）
+    #[inline]
+    pub fn advance(&mut self, n: u32) -> f32 {
+        if self.remaining > n {
+            self.current += self.step * n as f32;
+            self.remaining -= n;
+        } else if self.remaining > 0 {
+            self.current = self.target;
+            self.remaining = 0;
+        }
+        self.current
+    }
+}
@@ -0,0 +1,52 @@
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+
+/// ボイスごとのパンの散らし方
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SpreadMode {
+    #[default]
+    Key, // 低いノートほど左、高いノートほど右
+    Alternate, // 発音ごとに左右交互（同じキーに重なるレイヤーも広がる）
+}
+
+/// ステレオ出力の設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stereo {
+    pub pan: f32,    // -1.0（左）〜 1.0（右）
+    pub spread: f32, // 0.0〜1.0 ボイスごとにパンを散らす量
+    pub mode: SpreadMode,
+    pub width: f32, // マスターのステレオ幅 0.0（モノラル）〜 2.0
+}
+
+impl Default for Stereo {
+    fn default() -> Self {
+        Self {
+            pan: 0.0,
+            spread: 0.0,
+            mode: SpreadMode::Key,
+            width: 1.0,
+        }
+    }
+}
+
+const KEY_CENTER: f32 = 60.0;
+const KEY_RANGE: f32 = 24.0; // C2〜C6 で左右いっぱいに広がる
+
+/// SpreadMode::Key のときのノート位置（-1.0〜1.0）
+pub fn key_position(midi: f32) -> f32 {
+    ((midi - KEY_CENTER) / KEY_RANGE).clamp(-1.0, 1.0)
+}
+
+/// 等パワーのパン（中央で左右とも 0 dB）
+#[inline]
+pub fn pan_gains(pan: f32) -> [f32; 2] {
+    let (s, c) = ((pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4).sin_cos();
+    [c * SQRT_2, s * SQRT_2]
+}
+
+/// M/S でステレオ幅を変える
+#[inline]
+pub fn apply_width([l, r]: [f32; 2], width: f32) -> [f32; 2] {
+    let mid = 0.5 * (l + r);
+    let side = 0.5 * (l - r) * width;
+    [mid + side, mid - side]
+}
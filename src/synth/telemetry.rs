@@ -0,0 +1,54 @@
+use crate::synth::{adsr::EnvState, engine::MAX_VOICES, note::Note};
+
+/// 1 ボイスの状態
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VoiceInfo {
+    pub note: Note,
+    pub state: EnvState, // 鳴っていなければ Idle
+    pub level: f32,      // エンベロープの値（0.0〜1.0）
+}
+
+/// オーディオ側から GUI へ送る 1 ブロック分の様子
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Telemetry {
+    pub voices: [VoiceInfo; MAX_VOICES],
+    pub active_voices: u8,
+    pub peak: [f32; 2], // 出力のピーク（左右）
+    pub rms: [f32; 2],
+    pub cpu: f32,     // 描くのにかかった時間 / ブロックの長さ
+    pub dropped: u32, // 起動から今までに捨てたメッセージの数（漏らしたバッファは含まない）
+}
+
+impl Telemetry {
+    /// 後から届いた next をまとめる（ボイスは新しい方、ピークと CPU は大きい方）
+    pub fn merge(&mut self, next: &Telemetry) {
+        let peak = [0, 1].map(|ch| self.peak[ch].max(next.peak[ch]));
+        let cpu = self.cpu.max(next.cpu);
+        *self = Telemetry { peak, cpu, ..*next };
+    }
+}
+
+/// ブロックのピークと RMS を数える
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LevelMeter {
+    peak: [f32; 2],
+    sum_sq: [f32; 2],
+    frames: u32,
+}
+
+impl LevelMeter {
+    #[inline]
+    pub fn add(&mut self, frame: [f32; 2]) {
+        for (ch, x) in frame.into_iter().enumerate() {
+            self.peak[ch] = self.peak[ch].max(x.abs());
+            self.sum_sq[ch] += x * x;
+        }
+        self.frames += 1;
+    }
+
+    /// (ピーク, RMS)
+    pub fn levels(&self) -> ([f32; 2], [f32; 2]) {
+        let n = self.frames.max(1) as f32;
+        (self.peak, self.sum_sq.map(|s| (s / n).sqrt()))
+    }
+}
@@ -1,21 +1,24 @@
-// Thin AudioWorklet shim: consumes 128-frame Float32Array blocks from main thread.
+// Thin AudioWorklet shim: consumes planar stereo Float32Array blocks from main thread.
 // Keeps WASM DSP on main thread while avoiding ScriptProcessor deprecation.
 
 class SynthProcessor extends AudioWorkletProcessor {
   constructor() {
     super();
-    this.queue = [];          // Float32Array blocks (mono, length 128)
+    this.queue = [];          // { l, r } Float32Array pairs
     this.cur = null;          // { block, off }
     this.lowWater = 5;        // threshold to trigger refill
     this.target = 8;          // target buffered blocks after refill
     this.quantum = 128;       // expected block size
     this.port.onmessage = (e) => {
       const d = e.data || {};
+      if (d.left && d.left.length) {
+        this.queue.push({ l: d.left, r: d.right || d.left });
+      }
       if (d.mono && d.mono.length) {
-        this.queue.push(d.mono);
+        this.queue.push({ l: d.mono, r: d.mono });
       }
       if (Array.isArray(d.blocks)) {
-        for (const b of d.blocks) if (b && b.length) this.queue.push(b);
+        for (const b of d.blocks) if (b && b.length) this.queue.push({ l: b, r: b });
       }
       if (d.quantum) this.quantum = d.quantum|0;
     };
@@ -38,16 +41,21 @@ class SynthProcessor extends AudioWorkletProcessor {
       }
       const blk = this.cur.block;
       const off = this.cur.off;
-      const rem = blk.length - off;
+      const rem = blk.l.length - off;
       if (rem <= 0) { this.cur = null; continue; }
       const toCopy = Math.min(rem, frames - i);
       for (let k = 0; k < toCopy; k++) {
-        const s = blk[off + k] || 0;
-        l[i + k] = s;
-        if (out.length > 1) r[i + k] = s;
+        const sl = blk.l[off + k] || 0;
+        const sr = blk.r[off + k] || 0;
+        if (out.length > 1) {
+          l[i + k] = sl;
+          r[i + k] = sr;
+        } else {
+          l[i + k] = 0.5 * (sl + sr);
+        }
       }
       this.cur.off += toCopy;
-      if (this.cur.off >= blk.length) this.cur = null;
+      if (this.cur.off >= blk.l.length) this.cur = null;
       i += toCopy;
     }
 
@@ -56,7 +64,7 @@ class SynthProcessor extends AudioWorkletProcessor {
     // Approximate how many blocks are queued including current remainder
     let queued = this.queue.length;
     if (this.cur) {
-      const rem = Math.max(0, this.cur.block.length - this.cur.off);
+      const rem = Math.max(0, this.cur.block.l.length - this.cur.off);
       queued += Math.ceil(rem / this.quantum);
     }
     if (queued < this.lowWater) {